@@ -0,0 +1,467 @@
+use dyn_inventory_runtime::Registry;
+
+use crate::{Flag, ParseError, Spec, Takes, Value};
+
+/// a positional argument, declared by the application rather than by plugins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Positional {
+    pub name: &'static str,
+    pub help: &'static str,
+}
+
+/// a command line whose flags are the plugins of a registry.
+pub struct Cli<E> {
+    pub(crate) name: &'static str,
+    pub(crate) about: Option<&'static str>,
+    pub(crate) flags: Vec<E>,
+    pub(crate) positionals: Vec<Positional>,
+}
+
+/// a flag as it was passed, in command line order.
+#[derive(Debug)]
+pub struct Occurrence<'a, E> {
+    /// the plugin which declared the flag
+    pub flag: &'a E,
+    /// the value passed, parsed as the type of the flag. switches are passed `true`.
+    pub value: Value,
+}
+
+/// the flags and positional arguments of a parsed command line.
+#[derive(Debug)]
+pub struct Matches<'a, E> {
+    /// each flag passed, in command line order
+    pub flags: Vec<Occurrence<'a, E>>,
+    /// the positional arguments, in the order they were declared
+    pub positionals: Vec<String>,
+}
+
+/// the outcome of parsing a command line.
+#[derive(Debug)]
+pub enum Parsed<'a, E> {
+    /// `--help` or `-h` was passed, with the help to print
+    Help(String),
+    /// the flags and positional arguments to run with
+    Matches(Matches<'a, E>),
+}
+
+impl<E: Flag> Cli<E> {
+    /// a command line named `name`, whose flags are `flags`
+    pub fn new(
+        name: &'static str,
+        flags: Vec<E>,
+    ) -> Self {
+        Self {
+            name,
+            about: None,
+            flags,
+            positionals: Vec::new(),
+        }
+    }
+
+    /// a command line named `name`, whose flags are the plugins collected by `R`
+    pub fn from_registry<R: Registry<Entry = E>>(name: &'static str) -> Self {
+        Self::new(name, R::collect())
+    }
+
+    /// sets the description shown at the top of help
+    pub fn about(
+        mut self,
+        about: &'static str,
+    ) -> Self {
+        self.about = Some(about);
+        self
+    }
+
+    /// declares a required positional argument, after those already declared
+    pub fn positional(
+        mut self,
+        name: &'static str,
+        help: &'static str,
+    ) -> Self {
+        self.positionals
+            .push(Positional { name, help });
+        self
+    }
+
+    /// the plugins whose flags are parsed
+    pub fn flags(&self) -> &[E] {
+        &self.flags
+    }
+
+    /// parses `args`, whose first item is the program name, as from [`std::env::args`].
+    ///
+    /// long flags are passed as `--long value` or `--long=value`, and short flags as `-s value`,
+    /// `-s=value` or `-svalue`. short switches can be combined, as `-abc`. everything after `--` is
+    /// positional.
+    pub fn parse<I: IntoIterator<Item: Into<String>>>(
+        &self,
+        args: I,
+    ) -> Result<Parsed<'_, E>, ParseError> {
+        let mut args = args.into_iter().map(Into::into).skip(1);
+        let mut passed = vec![false; self.flags.len()];
+        let mut flags = Vec::new();
+        let mut positionals = Vec::new();
+
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                positionals.extend(args.by_ref());
+                break;
+            }
+            if arg == "--help" || arg == "-h" {
+                return Ok(Parsed::Help(self.help()));
+            }
+
+            if let Some(long) = arg.strip_prefix("--") {
+                let (name, inline) = match long.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (long, None),
+                };
+                let idx = self.find(&format!("--{name}"), |spec| spec.long == name)?;
+                let value = self.value(idx, inline, &mut args)?;
+                flags.push(self.occurrence(idx, value, &mut passed)?);
+            } else if let Some(shorts) = arg
+                .strip_prefix('-')
+                .filter(|shorts| !shorts.is_empty())
+            {
+                for (pos, short) in shorts.char_indices() {
+                    let idx = self.find(&format!("-{short}"), |spec| spec.short == Some(short))?;
+                    if self.flags[idx].spec().is_switch() {
+                        flags.push(self.occurrence(idx, Value::switch(), &mut passed)?);
+                        continue;
+                    }
+
+                    // the rest of the group is the value of the first short flag which takes one
+                    let rest = &shorts[pos + short.len_utf8()..];
+                    let rest = rest.strip_prefix('=').unwrap_or(rest);
+                    let inline = (!rest.is_empty()).then(|| rest.to_string());
+                    let value = self.value(idx, inline, &mut args)?;
+                    flags.push(self.occurrence(idx, value, &mut passed)?);
+                    break;
+                }
+            } else {
+                positionals.push(arg);
+            }
+        }
+
+        if let Some(missing) = self.positionals.get(positionals.len()) {
+            return Err(ParseError::MissingPositional { name: missing.name });
+        }
+        if let Some(extra) = positionals.get(self.positionals.len()) {
+            return Err(ParseError::UnexpectedPositional {
+                value: extra.clone(),
+            });
+        }
+
+        Ok(Parsed::Matches(Matches { flags, positionals }))
+    }
+
+    /// the position of the only plugin whose spec matches, where `flag` is the flag as passed
+    fn find(
+        &self,
+        flag: &str,
+        matches: impl Fn(&Spec) -> bool,
+    ) -> Result<usize, ParseError> {
+        let mut found = self
+            .flags
+            .iter()
+            .enumerate()
+            .filter(|(_, plugin)| matches(plugin.spec()))
+            .map(|(idx, _)| idx);
+        match (found.next(), found.next()) {
+            (Some(idx), None) => Ok(idx),
+            (Some(_), Some(_)) => {
+                Err(ParseError::Conflict {
+                    flag: flag.to_string(),
+                })
+            },
+            (None, _) => {
+                Err(ParseError::Unknown {
+                    flag: flag.to_string(),
+                })
+            },
+        }
+    }
+
+    /// the value of the flag at `idx`: the inline value, or for flags which take a value and were
+    /// passed without one, the next argument, parsed as the type of the flag
+    fn value(
+        &self,
+        idx: usize,
+        inline: Option<String>,
+        args: &mut impl Iterator<Item = String>,
+    ) -> Result<Value, ParseError> {
+        let spec = self.flags[idx].spec();
+        let raw = match (spec.takes, inline) {
+            (Takes::Switch, None) => return Ok(Value::switch()),
+            (Takes::Switch, Some(value)) => {
+                return Err(ParseError::UnexpectedValue {
+                    flag: spec.long,
+                    value,
+                });
+            },
+            (Takes::Value(_), Some(value)) => value,
+            (Takes::Value(_), None) => {
+                // a flag is never taken as a value, so `--name --other` reports the missing value,
+                // while other arguments starting with `-` are, such as `-5` for a number, and are
+                // reported as invalid when they don't parse
+                match args.next() {
+                    Some(value)
+                        if !value.starts_with('-') || value.len() == 1 || !self.is_flag(&value) =>
+                    {
+                        value
+                    },
+                    _ => return Err(ParseError::MissingValue { flag: spec.long }),
+                }
+            },
+        };
+        match self.flags[idx].parse(&raw) {
+            Ok(parsed) => Ok(parsed),
+            Err(reason) => {
+                Err(ParseError::InvalidValue {
+                    flag: spec.long,
+                    value: raw,
+                    reason,
+                })
+            },
+        }
+    }
+
+    /// whether `arg`, which starts with `-`, is a flag rather than a value: any long flag, or a
+    /// group of short flags starting with a declared one
+    fn is_flag(
+        &self,
+        arg: &str,
+    ) -> bool {
+        if arg.starts_with("--") {
+            return true;
+        }
+        let short = arg[1..].chars().next();
+        short == Some('h')
+            || self
+                .flags
+                .iter()
+                .any(|plugin| plugin.spec().short.is_some() && plugin.spec().short == short)
+    }
+
+    fn occurrence(
+        &self,
+        idx: usize,
+        value: Value,
+        passed: &mut [bool],
+    ) -> Result<Occurrence<'_, E>, ParseError> {
+        let flag = &self.flags[idx];
+        if passed[idx] && !flag.spec().repeated {
+            return Err(ParseError::Repeated {
+                flag: flag.spec().long,
+            });
+        }
+        passed[idx] = true;
+        Ok(Occurrence { flag, value })
+    }
+}
+
+impl<'a, E: Flag> Matches<'a, E> {
+    /// the occurrences of the flag `--long`, in command line order
+    pub fn occurrences(
+        &self,
+        long: &str,
+    ) -> impl Iterator<Item = &Occurrence<'a, E>> {
+        self.flags
+            .iter()
+            .filter(move |occurrence| occurrence.flag.spec().long == long)
+    }
+
+    /// whether the flag `--long` was passed
+    pub fn contains(
+        &self,
+        long: &str,
+    ) -> bool {
+        self.occurrences(long).next().is_some()
+    }
+
+    /// the values passed to the flag `--long`, in command line order
+    pub fn values(
+        &self,
+        long: &str,
+    ) -> impl Iterator<Item = &Value> {
+        self.occurrences(long)
+            .map(|occurrence| &occurrence.value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use crate::{Cli, Flag, ParseError, Parsed, Spec, Value};
+
+    struct Plugin(Spec);
+
+    impl Flag for Plugin {
+        fn spec(&self) -> &Spec {
+            &self.0
+        }
+    }
+
+    fn cli() -> Cli<Plugin> {
+        Cli::new(
+            "bin",
+            vec![
+                Plugin(Spec::value("name").short('n').repeated()),
+                Plugin(Spec::value("level").short('l').of::<u8>()),
+                Plugin(Spec::value("offset").of::<i32>()),
+                Plugin(Spec::switch("verbose").short('v')),
+                Plugin(Spec::switch("quiet").short('q')),
+            ],
+        )
+    }
+
+    /// each flag passed, as `long=value` or `long` for switches, then the positional arguments
+    fn parse(
+        cli: &Cli<Plugin>,
+        args: &[&str],
+    ) -> Result<Vec<String>, ParseError> {
+        let Parsed::Matches(matches) = cli.parse(["bin"].iter().chain(args).copied())? else {
+            panic!("help was not requested");
+        };
+        Ok(matches
+            .flags
+            .iter()
+            .map(|occurrence| {
+                let spec = occurrence.flag.spec();
+                if spec.is_switch() {
+                    spec.long.to_string()
+                } else {
+                    format!("{}={}", spec.long, occurrence.value.as_str())
+                }
+            })
+            .chain(matches.positionals)
+            .collect())
+    }
+
+    #[test_case(&["--name", "a"], &["name=a"]; "long with space")]
+    #[test_case(&["--name=a=b"], &["name=a=b"]; "long with equals")]
+    #[test_case(&["-n", "a"], &["name=a"]; "short with space")]
+    #[test_case(&["-n=a"], &["name=a"]; "short with equals")]
+    #[test_case(&["-na"], &["name=a"]; "short attached")]
+    #[test_case(&["--verbose", "-q"], &["verbose", "quiet"]; "switches")]
+    #[test_case(&["-vqn", "a"], &["verbose", "quiet", "name=a"]; "combined shorts")]
+    #[test_case(&["-vna"], &["verbose", "name=a"]; "combined shorts with value")]
+    #[test_case(&["-n", "a", "--name", "b"], &["name=a", "name=b"]; "repeated")]
+    #[test_case(&["--name", "-"], &["name=-"]; "dash as value")]
+    #[test_case(&["--name=-1"], &["name=-1"]; "dash prefixed value with equals")]
+    #[test_case(&["--offset", "-5"], &["offset=-5"]; "negative number")]
+    #[test_case(&["--name", "-x"], &["name=-x"]; "dash prefixed value")]
+    #[test_case(&[], &[]; "nothing")]
+    fn flags(
+        args: &[&str],
+        expected: &[&str],
+    ) {
+        assert_eq!(parse(&cli(), args).unwrap(), expected);
+    }
+
+    #[test_case(&["--other"], ParseError::Unknown { flag: "--other".into() }; "unknown long")]
+    #[test_case(&["--other=a"], ParseError::Unknown { flag: "--other".into() }; "unknown long with value")]
+    #[test_case(&["-vx"], ParseError::Unknown { flag: "-x".into() }; "unknown short")]
+    #[test_case(&["--level"], ParseError::MissingValue { flag: "level" }; "missing value")]
+    #[test_case(&["--level", "--verbose"], ParseError::MissingValue { flag: "level" }; "flag as value")]
+    #[test_case(&["--name", "-v"], ParseError::MissingValue { flag: "name" }; "short flag as value")]
+    #[test_case(&["--name", "--other"], ParseError::MissingValue { flag: "name" }; "unknown long flag as value")]
+    #[test_case(&["--offset", "-x"], ParseError::InvalidValue { flag: "offset", value: "-x".into(), reason: "invalid digit found in string".into() }; "unparsed dash prefixed value")]
+    #[test_case(&["--level", "x"], ParseError::InvalidValue { flag: "level", value: "x".into(), reason: "invalid digit found in string".into() }; "invalid value")]
+    #[test_case(&["-l256"], ParseError::InvalidValue { flag: "level", value: "256".into(), reason: "number too large to fit in target type".into() }; "out of range")]
+    #[test_case(&["--verbose=yes"], ParseError::UnexpectedValue { flag: "verbose", value: "yes".into() }; "switch with value")]
+    #[test_case(&["-l", "1", "-l", "2"], ParseError::Repeated { flag: "level" }; "repeated")]
+    #[test_case(&["-v", "-v"], ParseError::Repeated { flag: "verbose" }; "repeated switch")]
+    #[test_case(&["file"], ParseError::UnexpectedPositional { value: "file".into() }; "undeclared positional")]
+    fn errors(
+        args: &[&str],
+        expected: ParseError,
+    ) {
+        assert_eq!(parse(&cli(), args).unwrap_err(), expected);
+    }
+
+    #[test]
+    fn positionals() {
+        let cli = cli()
+            .positional("INPUT", "")
+            .positional("OUTPUT", "");
+        assert_eq!(
+            parse(&cli, &["a", "-v", "b"]).unwrap(),
+            ["verbose", "a", "b"]
+        );
+        assert_eq!(
+            parse(&cli, &["-v", "--", "--name", "-q"]).unwrap(),
+            ["verbose", "--name", "-q"]
+        );
+        assert_eq!(
+            parse(&cli, &["a"]).unwrap_err(),
+            ParseError::MissingPositional { name: "OUTPUT" }
+        );
+    }
+
+    #[test]
+    fn conflict() {
+        let cli = Cli::new(
+            "bin",
+            vec![
+                Plugin(Spec::switch("verbose")),
+                Plugin(Spec::switch("verbose")),
+            ],
+        );
+        assert_eq!(
+            parse(&cli, &["--verbose"]).unwrap_err(),
+            ParseError::Conflict {
+                flag: "--verbose".into()
+            }
+        );
+    }
+
+    #[test]
+    fn matches() {
+        let cli = cli();
+        let Parsed::Matches(matches) = cli
+            .parse(["bin", "-n", "a", "-v", "--name=b"])
+            .unwrap()
+        else {
+            panic!("help was not requested");
+        };
+        let names: Vec<_> = matches
+            .values("name")
+            .map(Value::as_str)
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+        assert!(matches.contains("verbose"));
+        assert!(!matches.contains("quiet"));
+    }
+
+    #[test]
+    fn typed() {
+        let cli = cli();
+        let Parsed::Matches(matches) = cli
+            .parse(["bin", "-l", "3", "-v", "-n", "4"])
+            .unwrap()
+        else {
+            panic!("help was not requested");
+        };
+        let values: Vec<_> = matches
+            .flags
+            .iter()
+            .map(|occurrence| &occurrence.value)
+            .collect();
+        assert_eq!(values[0].get::<u8>(), Some(&3));
+        assert_eq!(values[1].get::<bool>(), Some(&true));
+        assert_eq!(values[2].get::<String>().map(String::as_str), Some("4"));
+        assert!(values[2].get::<u8>().is_none());
+    }
+
+    #[test_case(&["--help"]; "long")]
+    #[test_case(&["-v", "-h", "--other"]; "short")]
+    fn help(args: &[&str]) {
+        let cli = cli();
+        let parsed = cli
+            .parse(["bin"].iter().chain(args).copied())
+            .unwrap();
+        assert!(matches!(parsed, Parsed::Help(help) if help == cli.help()));
+    }
+}
@@ -0,0 +1,52 @@
+/// the command line could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// no plugin declared the flag, as passed
+    Unknown { flag: String },
+    /// more than one plugin declared the flag, as passed
+    Conflict { flag: String },
+    /// a flag which takes a value was passed without one
+    MissingValue { flag: &'static str },
+    /// a value could not be parsed as the type of the flag, for the reason given by its `FromStr`
+    InvalidValue {
+        flag: &'static str,
+        value: String,
+        reason: String,
+    },
+    /// a switch was passed a value
+    UnexpectedValue { flag: &'static str, value: String },
+    /// a flag which isn't repeated was passed more than once
+    Repeated { flag: &'static str },
+    /// a declared positional argument was not passed
+    MissingPositional { name: &'static str },
+    /// more positional arguments were passed than were declared
+    UnexpectedPositional { value: String },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            Self::Unknown { flag } => write!(f, "unknown flag `{flag}`"),
+            Self::Conflict { flag } => {
+                write!(f, "flag `{flag}` is declared by more than one plugin")
+            },
+            Self::MissingValue { flag } => write!(f, "flag `--{flag}` requires a value"),
+            Self::InvalidValue {
+                flag,
+                value,
+                reason,
+            } => write!(f, "invalid value `{value}` for `--{flag}`: {reason}"),
+            Self::UnexpectedValue { flag, value } => {
+                write!(f, "flag `--{flag}` does not take a value, got `{value}`")
+            },
+            Self::Repeated { flag } => write!(f, "flag `--{flag}` was passed more than once"),
+            Self::MissingPositional { name } => write!(f, "missing argument <{name}>"),
+            Self::UnexpectedPositional { value } => write!(f, "unexpected argument `{value}`"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
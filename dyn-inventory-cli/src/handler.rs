@@ -0,0 +1,164 @@
+use std::{any::Any, fmt::Display, str::FromStr};
+
+use crate::Value;
+
+/// the handle of a flag plugin, passed its value as the type it declares.
+///
+/// the value is parsed as [`Handler::Value`] before any plugin is dispatched to, so the type is
+/// declared once, here, rather than also in the [`Spec`](crate::Spec). switches are passed `true`,
+/// so their handlers take a `bool`. `Cx` is what every plugin of the registry is passed alongside
+/// its value, such as the output to write to.
+///
+/// ```rust,ignore
+/// impl<'w> Handler<dyn Write + 'w> for Port {
+///     type Value = u16;
+///     type Output = std::io::Result<()>;
+///
+///     fn handle(&self, port: &u16, w: &mut (dyn Write + 'w)) -> std::io::Result<()> {
+///         writeln!(w, "listening on {port}")
+///     }
+/// }
+/// ```
+pub trait Handler<Cx: ?Sized> {
+    /// the type the value of the flag is parsed as
+    type Value: FromStr<Err: Display> + Any + Send + Sync;
+
+    /// what handling the flag returns, the same for every plugin of the registry
+    type Output;
+
+    fn handle(
+        &self,
+        value: &Self::Value,
+        cx: &mut Cx,
+    ) -> Self::Output;
+}
+
+/// a [`Handler`] with its value type erased, so that it can be the trait object of a registry.
+///
+/// implemented for every `Handler`. registries declare their trait as a subtrait of it, and parse
+/// the values of their flags with [`DynHandler::parse`], by overriding
+/// [`Flag::parse`](crate::Flag::parse):
+///
+/// ```rust,ignore
+/// pub trait Greeter: for<'w> DynHandler<dyn Write + 'w, std::io::Result<()>> {}
+///
+/// impl<H: for<'w> DynHandler<dyn Write + 'w, std::io::Result<()>>> Greeter for H {}
+///
+/// impl Flag for GreeterFlag {
+///     fn spec(&self) -> &Spec {
+///         &self.spec
+///     }
+///
+///     fn parse(&self, raw: &str) -> Result<Value, String> {
+///         self.handle.parse(raw)
+///     }
+/// }
+/// ```
+pub trait DynHandler<Cx: ?Sized, Output> {
+    /// parses `raw` as the value type of the handler
+    fn parse(
+        &self,
+        raw: &str,
+    ) -> Result<Value, String>;
+
+    /// handles a value this handler parsed.
+    ///
+    /// # Panics
+    ///
+    /// when `value` was not parsed by [`DynHandler::parse`], and is of another type than the value
+    /// type of the handler.
+    fn handle(
+        &self,
+        value: &Value,
+        cx: &mut Cx,
+    ) -> Output;
+}
+
+impl<Cx: ?Sized, H: Handler<Cx>> DynHandler<Cx, H::Output> for H {
+    fn parse(
+        &self,
+        raw: &str,
+    ) -> Result<Value, String> {
+        Value::parse::<H::Value>(raw)
+    }
+
+    fn handle(
+        &self,
+        value: &Value,
+        cx: &mut Cx,
+    ) -> H::Output {
+        let Some(parsed) = value.get::<H::Value>() else {
+            panic!(
+                "`{}` was not parsed as a `{}`, the value of the handler",
+                value.as_str(),
+                std::any::type_name::<H::Value>()
+            );
+        };
+        Handler::handle(self, parsed, cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Cli, DynHandler, Flag, Handler, Parsed, Spec, Value};
+
+    struct Offset;
+
+    impl Handler<Vec<i32>> for Offset {
+        type Value = i32;
+        type Output = ();
+
+        fn handle(
+            &self,
+            value: &i32,
+            out: &mut Vec<i32>,
+        ) {
+            out.push(*value);
+        }
+    }
+
+    struct Plugin(Spec, Box<dyn DynHandler<Vec<i32>, ()>>);
+
+    impl Flag for Plugin {
+        fn spec(&self) -> &Spec {
+            &self.0
+        }
+
+        fn parse(
+            &self,
+            raw: &str,
+        ) -> Result<Value, String> {
+            self.1.parse(raw)
+        }
+    }
+
+    #[test]
+    fn parses_as_the_value_of_the_handler() {
+        let cli = Cli::new(
+            "bin",
+            vec![Plugin(Spec::value("offset").repeated(), Box::new(Offset))],
+        );
+        let Parsed::Matches(matches) = cli
+            .parse(["bin", "--offset", "-5", "--offset=3"])
+            .unwrap()
+        else {
+            panic!("help was not requested");
+        };
+
+        let mut out = Vec::new();
+        for occurrence in matches.flags {
+            occurrence
+                .flag
+                .1
+                .handle(&occurrence.value, &mut out);
+        }
+        assert_eq!(out, [-5, 3]);
+    }
+
+    #[test]
+    #[should_panic = "`a` was not parsed as a `i32`, the value of the handler"]
+    fn panics_on_another_type() {
+        let value = Value::parse::<String>("a").unwrap();
+        DynHandler::handle(&Offset, &value, &mut Vec::new());
+    }
+}
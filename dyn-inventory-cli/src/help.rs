@@ -0,0 +1,124 @@
+use std::fmt::Write;
+
+use crate::{Cli, Flag, Spec, Takes};
+
+const HELP: Spec = Spec::switch("help")
+    .short('h')
+    .help("print help");
+
+impl<E: Flag> Cli<E> {
+    /// the help printed for `--help`: usage, then each positional argument and flag with its
+    /// description. flags are sorted by their long alias, as registration order is unspecified.
+    pub fn help(&self) -> String {
+        let mut specs: Vec<&Spec> = self.flags.iter().map(Flag::spec).collect();
+        specs.sort_by_key(|spec| spec.long);
+        specs.push(&HELP);
+
+        let flags: Vec<(String, &str)> = specs
+            .into_iter()
+            .map(|spec| (flag(spec), spec.help))
+            .collect();
+        let positionals: Vec<(String, &str)> = self
+            .positionals
+            .iter()
+            .map(|positional| (format!("<{}>", positional.name), positional.help))
+            .collect();
+        let width = flags
+            .iter()
+            .chain(&positionals)
+            .map(|(left, _)| left.len())
+            .max()
+            .unwrap_or_default();
+
+        let mut help = String::new();
+        if let Some(about) = self.about {
+            let _ = writeln!(help, "{about}\n");
+        }
+        let _ = write!(help, "Usage: {} [OPTIONS]", self.name);
+        for (positional, _) in &positionals {
+            let _ = write!(help, " {positional}");
+        }
+        help.push('\n');
+
+        for (heading, rows) in [("Arguments", &positionals), ("Options", &flags)] {
+            if rows.is_empty() {
+                continue;
+            }
+            let _ = writeln!(help, "\n{heading}:");
+            for (left, description) in rows {
+                let _ = if description.is_empty() {
+                    writeln!(help, "  {left}")
+                } else {
+                    writeln!(help, "  {left:width$}  {description}")
+                };
+            }
+        }
+        help
+    }
+}
+
+/// the aliases of a flag and its value, as `-s, --long <VALUE>...`
+fn flag(spec: &Spec) -> String {
+    let mut flag = match spec.short {
+        Some(short) => format!("-{short}, --{}", spec.long),
+        None => format!("    --{}", spec.long),
+    };
+    if let Takes::Value(name) = spec.takes {
+        let _ = write!(flag, " <{name}>");
+    }
+    if spec.repeated {
+        flag.push_str("...");
+    }
+    flag
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Cli, Flag, Spec};
+
+    struct Plugin(Spec);
+
+    impl Flag for Plugin {
+        fn spec(&self) -> &Spec {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn help() {
+        let cli = Cli::new(
+            "greeter",
+            vec![
+                Plugin(Spec::switch("wave").help("waves")),
+                Plugin(
+                    Spec::value("name")
+                        .short('n')
+                        .value_name("NAME")
+                        .help("greets someone")
+                        .repeated(),
+                ),
+                Plugin(Spec::value("bye")),
+            ],
+        )
+        .about("greets people")
+        .positional("TARGET", "who to greet");
+
+        assert_eq!(
+            cli.help(),
+            "\
+greets people
+
+Usage: greeter [OPTIONS] <TARGET>
+
+Arguments:
+  <TARGET>              who to greet
+
+Options:
+      --bye <VALUE>
+  -n, --name <NAME>...  greets someone
+      --wave            waves
+  -h, --help            print help
+"
+        );
+    }
+}
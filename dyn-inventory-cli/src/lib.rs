@@ -0,0 +1,79 @@
+//! command line parsing for clis whose flags are contributed by
+//! [`dyn-inventory`](https://docs.rs/dyn-inventory) plugins.
+//!
+//! each plugin of a flag registry carries a [`Spec`] as metadata, naming its long and short
+//! aliases, whether it is a switch or takes a value, whether it may be repeated, and its help. the
+//! registry implements [`Flag`] to expose it, and a [`Cli`] parses a command line against every
+//! collected plugin:
+//!
+//! ```rust,ignore
+//! use dyn_inventory_cli::{Cli, Flag, Parsed, Spec};
+//!
+//! dyn_inventory!(
+//!     GreeterFlag<Handle: Greeter> {
+//!         spec: Spec,
+//!         handle: Handle,
+//!     };
+//! );
+//!
+//! impl Flag for GreeterFlag {
+//!     fn spec(&self) -> &Spec {
+//!         &self.spec
+//!     }
+//! }
+//!
+//! emit! {
+//!     Hello Greeter as GreeterFlag {
+//!         spec = Spec::value("name").short('n').help("greets someone").repeated(),
+//!     }
+//! }
+//!
+//! let cli = Cli::from_registry::<GreeterFlagCollector>("greeter");
+//! match cli.parse(std::env::args())? {
+//!     Parsed::Help(help) => print!("{help}"),
+//!     Parsed::Matches(matches) => {
+//!         for occurrence in matches.flags {
+//!             occurrence.flag.handle.greet(&occurrence.value);
+//!         }
+//!     },
+//! }
+//! ```
+//!
+//! values are parsed before any plugin is dispatched to, as a `String`, or with [`Spec::of`] as any
+//! type implementing `FromStr`. plugins of one registry can take values of different types, so each
+//! is passed a [`Value`] with its type erased, and gets the parsed value back with [`Value::get`].
+//! handles implementing [`Handler`] instead declare the type of their value once, as
+//! [`Handler::Value`], which both parses the value and is what the handler is passed.
+//!
+//! unknown flags, missing or invalid values and unexpected positional arguments are reported as a
+//! [`ParseError`] rather than ignored. `--help` and `-h` are reserved, and render help from the
+//! specs of the collected plugins.
+
+mod cli;
+mod error;
+mod handler;
+mod help;
+mod spec;
+mod value;
+
+pub use cli::{Cli, Matches, Occurrence, Parsed};
+pub use error::ParseError;
+pub use handler::{DynHandler, Handler};
+pub use spec::{Spec, Takes};
+pub use value::Value;
+
+/// a plugin which contributes a flag to a [`Cli`], implemented for the generated `StructName` of a
+/// flag registry.
+pub trait Flag {
+    /// how the flag is spelled, what it takes, and how it is described in help
+    fn spec(&self) -> &Spec;
+
+    /// parses the value passed to the flag, as the type set by [`Spec::of`]. registries whose
+    /// handles are [`Handler`]s parse with the handle instead, through [`DynHandler::parse`].
+    fn parse(
+        &self,
+        raw: &str,
+    ) -> Result<Value, String> {
+        (self.spec().parse)(raw)
+    }
+}
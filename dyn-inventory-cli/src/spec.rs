@@ -0,0 +1,130 @@
+use std::{any::Any, fmt::Display, str::FromStr};
+
+use crate::Value;
+
+/// what a flag takes from the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Takes {
+    /// nothing, the flag is a boolean switch
+    Switch,
+    /// a value, shown in help as `<name>`
+    Value(&'static str),
+}
+
+/// how a flag is spelled, what it takes, and how it is described in help.
+///
+/// each plugin of a flag registry carries a `Spec` as metadata. the builder methods are `const`, so
+/// a spec can be written directly in `emit!`:
+///
+/// ```rust,ignore
+/// emit! {
+///     Handle Greeter as Flag {
+///         spec = Spec::value("name").short('n').help("greets someone").repeated(),
+///     }
+/// }
+///
+/// emit! {
+///     Port Server as Flag {
+///         spec = Spec::value("port").of::<u16>(),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Spec {
+    /// the long alias, `--long`
+    pub long: &'static str,
+    /// the short alias, `-s`
+    pub short: Option<char>,
+    /// the description shown in help
+    pub help: &'static str,
+    /// what the flag takes
+    pub takes: Takes,
+    /// whether the flag may be passed more than once
+    pub repeated: bool,
+    /// parses the value of the flag, as set by [`Spec::of`]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) parse: fn(&str) -> Result<Value, String>,
+}
+
+impl Spec {
+    /// a flag which takes a value, `--long <VALUE>` or `--long=<VALUE>`. the value is a `String`,
+    /// unless another type is set with [`Spec::of`].
+    pub const fn value(long: &'static str) -> Self {
+        Self {
+            long,
+            short: None,
+            help: "",
+            takes: Takes::Value("VALUE"),
+            repeated: false,
+            parse: Value::parse::<String>,
+        }
+    }
+
+    /// a boolean switch, `--long`. its value is `true`.
+    pub const fn switch(long: &'static str) -> Self {
+        Self {
+            takes: Takes::Switch,
+            ..Self::value(long)
+        }
+    }
+
+    /// adds a short alias, `-s`
+    pub const fn short(
+        self,
+        short: char,
+    ) -> Self {
+        Self {
+            short: Some(short),
+            ..self
+        }
+    }
+
+    /// sets the description shown in help
+    pub const fn help(
+        self,
+        help: &'static str,
+    ) -> Self {
+        Self { help, ..self }
+    }
+
+    /// sets the name of the value shown in help. has no effect on switches.
+    pub const fn value_name(
+        self,
+        name: &'static str,
+    ) -> Self {
+        match self.takes {
+            Takes::Switch => self,
+            Takes::Value(_) => {
+                Self {
+                    takes: Takes::Value(name),
+                    ..self
+                }
+            },
+        }
+    }
+
+    /// parses the value of the flag as `T` before it is dispatched, so that a value `T` can't be
+    /// parsed from is reported as a [`ParseError::InvalidValue`](crate::ParseError::InvalidValue).
+    /// has no effect on switches.
+    pub const fn of<T: FromStr<Err: Display> + Any + Send + Sync>(self) -> Self {
+        Self {
+            parse: Value::parse::<T>,
+            ..self
+        }
+    }
+
+    /// allows the flag to be passed more than once
+    pub const fn repeated(self) -> Self {
+        Self {
+            repeated: true,
+            ..self
+        }
+    }
+
+    /// whether the flag is a boolean switch
+    pub const fn is_switch(&self) -> bool {
+        matches!(self.takes, Takes::Switch)
+    }
+}
@@ -0,0 +1,65 @@
+use std::{any::Any, fmt::Display, str::FromStr};
+
+/// a parsed value, before its type is erased
+pub(crate) type Erased = Box<dyn Any + Send + Sync>;
+
+/// the value of a flag, parsed as the type set by [`Spec::of`](crate::Spec::of), or the value type
+/// of a [`Handler`](crate::Handler).
+///
+/// plugins of one registry can take values of different types, so the type is erased, and each
+/// plugin gets its value back with [`Value::get`]. the value was parsed by the spec of the plugin
+/// it is passed to, so `get` with the type the spec declared always succeeds.
+pub struct Value {
+    raw: String,
+    parsed: Erased,
+}
+
+impl Value {
+    pub(crate) fn new(
+        raw: String,
+        parsed: Erased,
+    ) -> Self {
+        Self { raw, parsed }
+    }
+
+    /// parses `raw` as `T`, or gives the reason it can't be
+    pub(crate) fn parse<T: FromStr<Err: Display> + Any + Send + Sync>(
+        raw: &str
+    ) -> Result<Self, String> {
+        match raw.parse::<T>() {
+            Ok(parsed) => Ok(Self::new(raw.to_string(), Box::new(parsed))),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    /// the value of a switch
+    pub(crate) fn switch() -> Self {
+        Self::new("true".into(), Box::new(true))
+    }
+
+    /// the value as passed on the command line
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// the parsed value, if it was parsed as `T`
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.parsed.downcast_ref()
+    }
+
+    /// whether the value was parsed as `T`
+    pub fn is<T: Any>(&self) -> bool {
+        self.parsed.is::<T>()
+    }
+}
+
+impl std::fmt::Debug for Value {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_tuple("Value")
+            .field(&self.raw)
+            .finish()
+    }
+}
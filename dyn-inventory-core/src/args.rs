@@ -0,0 +1,730 @@
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::{
+    Attribute, Ident, LitBool, LitStr, Token, Type, TypePath, TypeReference, Visibility, braced,
+    parse::Parse, punctuated::Punctuated,
+};
+
+use crate::backend::Backend;
+
+fn parse_vis(input: syn::parse::ParseStream) -> Visibility {
+    if input.peek(Token![pub]) {
+        input.parse().unwrap()
+    } else {
+        Visibility::Inherited
+    }
+}
+
+/// the type of a declared field: a path, such as the handle parameter, or a reference.
+pub enum RefOrTy {
+    /// a type path, such as `Handle` or `u32`
+    Ty(TypePath),
+    /// a reference, such as `&'static str`
+    Ref(TypeReference),
+}
+
+impl ToTokens for RefOrTy {
+    fn to_tokens(
+        &self,
+        tokens: &mut proc_macro2::TokenStream,
+    ) {
+        tokens.extend(match self {
+            Self::Ref(reffed) => {
+                let lt = reffed
+                    .lifetime
+                    .clone()
+                    .map(|ok| quote::quote! {#ok})
+                    .unwrap_or_default();
+
+                let ty = *reffed.elem.clone();
+
+                let mutability = reffed
+                    .mutability
+                    .map(|mt| quote::quote!(#mt))
+                    .unwrap_or_default();
+
+                quote::quote! {
+                    & #lt #mutability #ty
+                }
+            },
+            Self::Ty(ty) => quote::quote!(#ty),
+        });
+    }
+}
+
+impl Parse for RefOrTy {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![&]) {
+            Ok(Self::Ref(input.parse()?))
+        } else {
+            Ok(Self::Ty(input.parse()?))
+        }
+    }
+}
+
+/// a field of the registry struct, `[#[attr]] [vis] name: Type`.
+pub struct Field {
+    /// attributes forwarded to the generated fields, such as doc comments
+    pub attrs: Vec<Attribute>,
+    /// `#[serde(..)]` attributes, applied to the metadata view only
+    pub serde_attrs: Vec<Attribute>,
+    /// the visibility of the field on the registry struct
+    pub vis: Visibility,
+    /// the name of the field
+    pub name: Ident,
+    #[allow(unused)]
+    sep: Token![:],
+    /// the type of the field. exactly one field has the type of the handle parameter.
+    pub ty: RefOrTy,
+}
+
+impl Parse for Field {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let (serde_attrs, attrs) = input
+            .call(Attribute::parse_outer)?
+            .into_iter()
+            .partition(|attr| attr.path().is_ident("serde"));
+
+        Ok(Self {
+            attrs,
+            serde_attrs,
+            vis: parse_vis(input),
+            name: input.parse()?,
+            sep: input.parse()?,
+            ty: input.parse()?,
+        })
+    }
+}
+
+mod kw {
+    syn::custom_keyword!(init_name);
+    syn::custom_keyword!(key);
+    syn::custom_keyword!(depends_on);
+    syn::custom_keyword!(env_prefix);
+    syn::custom_keyword!(downcast);
+    syn::custom_keyword!(api_version);
+    syn::custom_keyword!(async_factory);
+    syn::custom_keyword!(error);
+    syn::custom_keyword!(catch_unwind);
+    syn::custom_keyword!(dylib);
+    syn::custom_keyword!(tags);
+    syn::custom_keyword!(backend);
+    syn::custom_keyword!(clap);
+    syn::custom_keyword!(help);
+    syn::custom_keyword!(lifecycle);
+    syn::custom_keyword!(priority);
+    syn::custom_keyword!(config);
+    syn::custom_keyword!(serde);
+}
+
+#[allow(non_camel_case_types)]
+enum ExtraOpts {
+    init_name { value: Ident },
+    key { value: Ident },
+    depends_on { value: Ident },
+    env_prefix { value: LitStr },
+    downcast { value: LitBool },
+    api_version { value: LitStr },
+    async_factory { value: LitBool },
+    error { value: Type },
+    catch_unwind { value: LitBool },
+    dylib { value: LitBool },
+    tags { value: Ident },
+    backend { value: Ident },
+    clap { value: Ident },
+    help { value: Ident },
+    lifecycle { value: LitBool },
+    priority { value: Ident },
+    config { value: LitBool },
+    serde { value: LitBool },
+}
+
+impl Parse for ExtraOpts {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        kws! {
+            input & [
+                init_name,
+                key,
+                depends_on,
+                env_prefix,
+                downcast,
+                api_version,
+                async_factory,
+                error,
+                catch_unwind,
+                dylib,
+                tags,
+                backend,
+                clap,
+                help,
+                lifecycle,
+                priority,
+                config,
+                serde,
+            ]
+        }
+    }
+}
+
+/// a registry declaration, the input of `dyn_inventory!`.
+///
+/// `Plugin<Handle: Trait> { fields }`, followed by `; option = value, ..`. options which name a
+/// field are checked to name a metadata field, i.e. not the handle.
+pub struct Args {
+    /// the registry struct, such as `Plugin`
+    pub struct_name: Ident,
+
+    /// the dyn-compatible trait every handle implements
+    pub trait_name: Ident,
+
+    /// the type parameter standing for the handle, such as `Handle`
+    pub generic_param: Ident,
+
+    #[allow(unused)]
+    brace: syn::token::Brace,
+
+    /// the fields of the registry struct, including the handle
+    pub fields: Punctuated<Field, Token![,]>,
+
+    #[allow(unused)]
+    term: Option<Token![;]>,
+
+    opts: Punctuated<ExtraOpts, Token![,]>,
+
+    /// the registration struct, `{struct_name}Init` unless `init_name` is set
+    pub init_name: Ident,
+
+    /// the field plugins are looked up and selected by
+    pub key: Option<Ident>,
+
+    /// the field listing the keys each plugin depends on
+    pub depends_on: Option<Ident>,
+
+    /// the prefix of the environment variables which enable and disable plugins
+    pub env_prefix: Option<LitStr>,
+
+    /// whether handles can be downcast to their concrete type
+    pub downcast: bool,
+
+    /// the version of the plugin api, checked against every registration
+    pub api_version: Option<LitStr>,
+
+    /// whether factories return futures
+    pub async_factory: bool,
+
+    /// the error factories fail with
+    pub error: Option<Type>,
+
+    /// whether panicking factories are caught and reported
+    pub catch_unwind: bool,
+
+    /// whether plugins can be loaded from dynamic libraries
+    pub dylib: bool,
+
+    /// the field listing the tags of each plugin
+    pub tags: Option<Ident>,
+
+    /// how registrations are submitted and collected
+    pub backend: Backend,
+
+    /// the field naming the command line argument or subcommand of each plugin
+    pub clap: Option<Ident>,
+
+    /// the field describing the argument named by `clap`
+    pub help: Option<Ident>,
+
+    /// whether plugins are started and stopped by a managed collector
+    pub lifecycle: bool,
+
+    /// the field ordering plugins before dependencies are resolved
+    pub priority: Option<Ident>,
+
+    /// whether plugins are configured from sections of a config
+    pub config: bool,
+
+    /// whether the metadata of each plugin is serializable with `serde`
+    pub serde: bool,
+}
+
+impl Parse for Args {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let struct_name: Ident = input.parse()?;
+        let _: Token![<] = input.parse()?;
+
+        let generic_param: Ident = input.parse()?;
+        let _: Token![:] = input.parse()?;
+
+        let trait_name: Ident = input.parse()?;
+        let _: Token![>] = input.parse()?;
+
+        let init_name = crate::init(&struct_name);
+
+        let content;
+        let brace = braced!(content in input);
+        let fields = content.parse_terminated(Field::parse, Token![,])?;
+
+        let term = match input.parse() {
+            Ok(cont) => Some(cont),
+            _ => {
+                if !input.is_empty() {
+                    return Err(syn::Error::new(input.span(), "expected `;`"));
+                } else {
+                    None
+                }
+            },
+        };
+
+        let opts = if !input.is_empty() {
+            input.parse_terminated(ExtraOpts::parse, Token![,])?
+        } else {
+            Punctuated::new()
+        };
+
+        let mut this = Self {
+            init_name,
+            trait_name,
+            struct_name,
+            generic_param,
+            brace,
+            fields,
+            term,
+            opts,
+            key: None,
+            depends_on: None,
+            env_prefix: None,
+            downcast: false,
+            api_version: None,
+            async_factory: false,
+            error: None,
+            catch_unwind: false,
+            dylib: false,
+            tags: None,
+            backend: Backend::default(),
+            clap: None,
+            help: None,
+            lifecycle: false,
+            priority: None,
+            config: false,
+            serde: false,
+        };
+
+        let mut config = None;
+        for opt in &this.opts {
+            match opt {
+                ExtraOpts::init_name { value, .. } => {
+                    this.init_name = value.clone();
+                },
+                ExtraOpts::key { value, .. } => {
+                    this.key = Some(this.typed_field(value, "&'static str")?);
+                },
+                ExtraOpts::depends_on { value, .. } => {
+                    this.depends_on = Some(this.typed_field(value, "&'static [&'static str]")?);
+                },
+                ExtraOpts::env_prefix { value, .. } => {
+                    this.env_prefix = Some(value.clone());
+                },
+                ExtraOpts::downcast { value, .. } => {
+                    this.downcast = value.value;
+                },
+                ExtraOpts::api_version { value, .. } => {
+                    if let Err(err) =
+                        semver::Version::parse(&dyn_inventory_runtime::complete(&value.value()))
+                    {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            format!("invalid api version: {err}"),
+                        ));
+                    }
+                    this.api_version = Some(value.clone());
+                },
+                ExtraOpts::async_factory { value, .. } => {
+                    this.async_factory = value.value;
+                },
+                ExtraOpts::error { value, .. } => {
+                    this.error = Some(value.clone());
+                },
+                ExtraOpts::catch_unwind { value, .. } => {
+                    this.catch_unwind = value.value;
+                },
+                ExtraOpts::dylib { value, .. } => {
+                    this.dylib = value.value;
+                },
+                ExtraOpts::tags { value, .. } => {
+                    this.tags = Some(this.typed_field(value, "&'static [&'static str]")?);
+                },
+                ExtraOpts::backend { value, .. } => {
+                    this.backend = Backend::parse(value)?;
+                },
+                ExtraOpts::clap { value, .. } => {
+                    this.clap = Some(this.typed_field(value, "&'static str")?);
+                },
+                ExtraOpts::help { value, .. } => {
+                    this.help = Some(this.typed_field(value, "&'static str")?);
+                },
+                ExtraOpts::lifecycle { value, .. } => {
+                    this.lifecycle = value.value;
+                },
+                ExtraOpts::priority { value, .. } => {
+                    this.priority = Some(this.metadata_field(value)?);
+                },
+                ExtraOpts::config { value, .. } => {
+                    this.config = value.value;
+                    config = Some(value.span());
+                },
+                ExtraOpts::serde { value, .. } => {
+                    this.serde = value.value;
+                },
+            }
+        }
+
+        if let (None, Some(depends_on)) = (&this.key, &this.depends_on) {
+            return Err(syn::Error::new(
+                depends_on.span(),
+                "`depends_on` requires a `key` field to resolve dependencies against",
+            ));
+        }
+
+        if let (None, Some(env_prefix)) = (&this.key, &this.env_prefix) {
+            return Err(syn::Error::new(
+                env_prefix.span(),
+                "`env_prefix` requires a `key` field to select plugins by",
+            ));
+        }
+
+        if let (None, true, Some(config)) = (&this.key, this.config, config) {
+            return Err(syn::Error::new(
+                config,
+                "`config` requires a `key` field to find the section of each plugin",
+            ));
+        }
+
+        if let (None, Some(help)) = (&this.clap, &this.help) {
+            return Err(syn::Error::new(
+                help.span(),
+                "`help` requires a `clap` field to name the argument it describes",
+            ));
+        }
+
+        if this.handle_fields().count() != 1 {
+            return Err(syn::Error::new(
+                this.generic_param.span(),
+                format!("exactly one field must have type `{}`", this.generic_param),
+            ));
+        }
+
+        Ok(this)
+    }
+}
+
+impl Args {
+    /// the name of a generated item, derived from the registry struct name
+    pub fn derived_name(
+        &self,
+        suffix: &str,
+    ) -> Ident {
+        Ident::new(&format!("{}{suffix}", self.struct_name), Span::call_site())
+    }
+
+    /// the fields whose type is the generic parameter, i.e. the plugin handle
+    fn handle_fields(&self) -> impl Iterator<Item = &Field> {
+        let generic = self.generic_param.to_string();
+        self.fields
+            .iter()
+            .filter(move |f| f.ty.to_token_stream().to_string() == generic)
+    }
+
+    /// the field holding the plugin handle
+    pub fn handle_field(&self) -> &Field {
+        self.handle_fields()
+            .next()
+            .expect("exactly one handle field is checked while parsing")
+    }
+
+    /// resolves an option value to one of the declared metadata fields (i.e. not the handle)
+    fn metadata_field(
+        &self,
+        name: &Ident,
+    ) -> syn::Result<Ident> {
+        let generic = self.generic_param.to_string();
+        match self.fields.iter().find(|f| f.name == *name) {
+            Some(f) if f.ty.to_token_stream().to_string() == generic => {
+                Err(syn::Error::new(
+                    name.span(),
+                    format!("`{name}` is the plugin handle and cannot be used as metadata"),
+                ))
+            },
+            Some(_) => Ok(name.clone()),
+            None => {
+                Err(syn::Error::new(
+                    name.span(),
+                    format!("no field named `{name}` is declared"),
+                ))
+            },
+        }
+    }
+
+    /// checks that `name` is a metadata field declared as `ty`, for the options which read it
+    /// directly, rather than through a trait.
+    fn typed_field(
+        &self,
+        name: &Ident,
+        ty: &str,
+    ) -> syn::Result<Ident> {
+        let name = self.metadata_field(name)?;
+        let expected = syn::parse_str::<Type>(ty)?
+            .to_token_stream()
+            .to_string();
+        match self.fields.iter().find(|f| f.name == name) {
+            Some(f) if f.ty.to_token_stream().to_string() == expected => Ok(name),
+            _ => {
+                Err(syn::Error::new(
+                    name.span(),
+                    format!("`{name}` must be declared as `{ty}`"),
+                ))
+            },
+        }
+    }
+
+    /// the type returned by the factory of each registration
+    pub(crate) fn factory_output(&self) -> TokenStream {
+        let output = self.handle_output();
+        if self.config {
+            quote::quote! { ::dyn_inventory_runtime::config::Configurable<#output> }
+        } else {
+            output
+        }
+    }
+
+    /// the type a factory builds the handle with, once it is configured
+    pub(crate) fn handle_output(&self) -> TokenStream {
+        let trt = &self.trait_name;
+        let output = match &self.error {
+            Some(err) => {
+                quote::quote! {
+                    ::core::result::Result<::dyn_inventory_runtime::alloc::boxed::Box<dyn #trt>, #err>
+                }
+            },
+            None => quote::quote! { ::dyn_inventory_runtime::alloc::boxed::Box<dyn #trt> },
+        };
+        if self.async_factory {
+            quote::quote! { ::dyn_inventory_runtime::BoxFuture<'static, #output> }
+        } else {
+            output
+        }
+    }
+
+    pub(crate) fn collect_fields_or_generic<
+        F: Fn(&Field) -> TokenStream,
+        G: Fn(&Field) -> TokenStream,
+    >(
+        &self,
+        fh: F,
+        gh: G,
+        generic: String,
+    ) -> TokenStream {
+        let mut tt = quote::quote! {};
+        let mut gg = quote::quote! {};
+        for f in &self.fields {
+            let field_ty = &f.ty;
+            if quote::quote!(#field_ty).to_string() == generic {
+                gg.extend(gh(f));
+            } else {
+                tt.extend(fh(f));
+            }
+        }
+        tt.extend(gg);
+        tt
+    }
+}
+
+impl ToTokens for Args {
+    fn to_tokens(
+        &self,
+        tokens: &mut TokenStream,
+    ) {
+        let vis = Visibility::Public(syn::token::Pub(Span::call_site()));
+        let trt = &self.trait_name;
+        let strct = &self.struct_name;
+        let generic = &self.generic_param;
+        let generic_str = generic.to_string();
+        let struct_init = &self.init_name;
+
+        let factory_output = self.factory_output();
+
+        let fields_init: proc_macro2::TokenStream = self.collect_fields_or_generic(
+            |Field {
+                 attrs,
+                 vis,
+                 name,
+                 ty,
+                 ..
+             }| {
+                quote::quote! {
+                    #(#attrs)*
+                    #vis #name: #ty,
+                }
+            },
+            |Field { vis, .. }| {
+                quote::quote! {
+                    #vis __get: ::dyn_inventory_runtime::Constructor<#factory_output>,
+                }
+            },
+            generic_str.clone(),
+        );
+
+        let fields_final: proc_macro2::TokenStream = self.collect_fields_or_generic(
+            |Field {
+                 attrs,
+                 vis,
+                 name,
+                 ty,
+                 ..
+             }| {
+                quote::quote! {
+                    #(#attrs)*
+                    #vis #name: #ty,
+                }
+            },
+            |Field {
+                 attrs, vis, name, ..
+             }| {
+                quote::quote! {
+                    #(#attrs)*
+                    #vis #name: ::dyn_inventory_runtime::alloc::boxed::Box<dyn #trt>,
+                }
+            },
+            generic_str.clone(),
+        );
+
+        let api_version = match &self.api_version {
+            Some(version) => quote::quote! { ::core::option::Option::Some(#version) },
+            None => quote::quote! { ::core::option::Option::None },
+        };
+
+        // the section a configured plugin was built with, to build further instances with
+        let section_field = self.config.then(|| {
+            quote::quote! {
+                __section: ::dyn_inventory_runtime::config::Section,
+            }
+        });
+
+        let linkme = self.backend == Backend::Linkme;
+        let iter = self.backend.iter(struct_init);
+
+        let struct_def = quote::quote! {
+            #[derive(::core::clone::Clone)]
+            #vis struct #struct_init {
+                #fields_init
+                #[doc(hidden)]
+                pub __source: ::dyn_inventory_runtime::Source,
+                #[doc(hidden)]
+                pub __api_version: ::core::option::Option<&'static str>,
+            }
+
+            impl #struct_init {
+                /// the api version declared by the registry, which `emit!` stamps into each registration
+                pub const API_VERSION: ::core::option::Option<&'static str> = #api_version;
+
+                #[doc(hidden)]
+                pub const __LINKME: bool = #linkme;
+
+                /// every registration submitted at link time
+                pub fn iter() -> impl ::core::iter::Iterator<Item = &'static Self> {
+                    #iter
+                }
+
+                /// the api version this plugin was built against
+                pub fn api_version(&self) -> ::core::option::Option<&'static str> {
+                    self.__api_version
+                }
+
+                /// where this plugin was registered
+                pub fn source(&self) -> &::dyn_inventory_runtime::Source {
+                    &self.__source
+                }
+            }
+
+            #vis struct #strct {
+                #fields_final
+                __get: ::dyn_inventory_runtime::Constructor<#factory_output>,
+                __source: ::dyn_inventory_runtime::Source,
+                #section_field
+            }
+
+            impl #strct {
+                /// where this plugin was registered
+                pub fn source(&self) -> &::dyn_inventory_runtime::Source {
+                    &self.__source
+                }
+            }
+        };
+
+        let collect = self.backend.declare(struct_init);
+
+        let plugin_collector = self.derived_name("Collector");
+        let plugin_collector_name = &plugin_collector;
+
+        let plugin_collector = crate::collector::expand(self);
+
+        let factory = crate::factory::expand(self);
+
+        let metadata = crate::metadata::expand(self);
+
+        let handle = &self.handle_field().name;
+        let (collect_error, try_collect) = match (&self.key, &self.depends_on) {
+            (Some(_), Some(_)) => {
+                let err = self.derived_name("DependencyError");
+                (
+                    quote::quote! { #err },
+                    quote::quote! {
+                        ::core::result::Result::map(Self::try_new(), |collected| collected.plugins)
+                    },
+                )
+            },
+            _ => {
+                (
+                    quote::quote! { ::core::convert::Infallible },
+                    quote::quote! { ::core::result::Result::Ok(Self::new().plugins) },
+                )
+            },
+        };
+        let runtime = quote::quote! {
+            impl ::dyn_inventory_runtime::Entry for #strct {
+                type Handle = dyn #trt;
+
+                fn handle(&self) -> &Self::Handle {
+                    &*self.#handle
+                }
+
+                fn source(&self) -> &::dyn_inventory_runtime::Source {
+                    &self.__source
+                }
+            }
+
+            impl ::dyn_inventory_runtime::Registry for #plugin_collector_name {
+                type Entry = #strct;
+                type Init = #struct_init;
+                type Handle = dyn #trt;
+                type Error = #collect_error;
+
+                fn try_collect() -> ::core::result::Result<::dyn_inventory_runtime::alloc::vec::Vec<Self::Entry>, Self::Error> {
+                    #try_collect
+                }
+            }
+        };
+
+        tokens.extend(quote::quote! {
+            #struct_def
+
+            #factory
+
+            #metadata
+
+            #collect
+
+            #plugin_collector
+
+            #runtime
+        });
+    }
+}
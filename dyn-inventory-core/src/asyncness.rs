@@ -0,0 +1,194 @@
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::{
+    FnArg, GenericParam, ImplItem, Item, Lifetime, LifetimeParam, ReturnType, Signature, Token,
+    TraitItem, Type, parse::Parse, parse_quote, visit_mut::VisitMut,
+};
+
+/// the arguments of `#[dyn_async]`: empty, or `?Send`
+pub struct DynAsyncArgs {
+    /// whether the returned futures are `Send`, i.e. `?Send` was not passed
+    pub send: bool,
+}
+
+impl Parse for DynAsyncArgs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.is_empty() {
+            return Ok(Self { send: true });
+        }
+
+        let _: Token![?] = input.parse()?;
+        let send: syn::Ident = input.parse()?;
+        if send != "Send" || !input.is_empty() {
+            return Err(syn::Error::new(send.span(), "expected `?Send`"));
+        }
+        Ok(Self { send: false })
+    }
+}
+
+/// the lifetime every elided lifetime of a rewritten method is tied to, and which bounds the future
+fn lifetime() -> Lifetime {
+    Lifetime::new("'dyn_async", Span::call_site())
+}
+
+/// names the elided lifetimes of a signature, so that the returned future can borrow them
+struct NameElided;
+
+impl VisitMut for NameElided {
+    fn visit_type_reference_mut(
+        &mut self,
+        reference: &mut syn::TypeReference,
+    ) {
+        if reference.lifetime.is_none() {
+            reference.lifetime = Some(lifetime());
+        }
+        syn::visit_mut::visit_type_reference_mut(self, reference);
+    }
+
+    fn visit_lifetime_mut(
+        &mut self,
+        lt: &mut Lifetime,
+    ) {
+        if lt.ident == "_" {
+            *lt = lifetime();
+        }
+    }
+
+    // lifetimes elided within fn pointers and `Fn` bounds are higher-ranked, and stay elided
+    fn visit_type_bare_fn_mut(
+        &mut self,
+        _: &mut syn::TypeBareFn,
+    ) {
+    }
+
+    fn visit_parenthesized_generic_arguments_mut(
+        &mut self,
+        _: &mut syn::ParenthesizedGenericArguments,
+    ) {
+    }
+}
+
+/// rewrites `async fn f(&self, ..) -> T` into `fn f<'dyn_async>(&'dyn_async self, ..) -> BoxFuture<'dyn_async, T>`
+fn rewrite_signature(
+    sig: &mut Signature,
+    send: bool,
+) {
+    let lt = lifetime();
+    sig.asyncness = None;
+
+    let bounds: Vec<syn::WherePredicate> = sig
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| {
+            match param {
+                GenericParam::Lifetime(param) => {
+                    let param = &param.lifetime;
+                    Some(parse_quote!(#param: #lt))
+                },
+                GenericParam::Type(param) => {
+                    let param = &param.ident;
+                    Some(parse_quote!(#param: #lt))
+                },
+                GenericParam::Const(_) => None,
+            }
+        })
+        .collect();
+    sig.generics
+        .make_where_clause()
+        .predicates
+        .extend(bounds);
+    sig.generics
+        .params
+        .insert(0, GenericParam::Lifetime(LifetimeParam::new(lt.clone())));
+
+    for input in &mut sig.inputs {
+        match input {
+            FnArg::Receiver(receiver) => {
+                if let Some((_, receiver_lt @ None)) = &mut receiver.reference {
+                    *receiver_lt = Some(lt.clone());
+                }
+                NameElided.visit_type_mut(&mut receiver.ty);
+            },
+            FnArg::Typed(typed) => NameElided.visit_type_mut(&mut typed.ty),
+        }
+    }
+
+    let mut output: Type = match &sig.output {
+        ReturnType::Default => parse_quote!(()),
+        ReturnType::Type(_, ty) => (**ty).clone(),
+    };
+    NameElided.visit_type_mut(&mut output);
+
+    sig.output = if send {
+        parse_quote!(-> ::dyn_inventory_runtime::BoxFuture<#lt, #output>)
+    } else {
+        parse_quote!(-> ::dyn_inventory_runtime::LocalBoxFuture<#lt, #output>)
+    };
+}
+
+/// bounds `Self` so that a `Send` future can capture the receiver of a default method: `&self` is
+/// shared across awaits, so `Self: Sync`, and `&mut self` or `self` is sent, so `Self: Send`
+fn bound_receiver(sig: &mut Signature) {
+    let Some(receiver) = sig.receiver() else {
+        return;
+    };
+    let bound: syn::WherePredicate = match (&receiver.reference, &receiver.mutability) {
+        (Some(_), None) => parse_quote!(Self: ::core::marker::Sync),
+        _ => parse_quote!(Self: ::core::marker::Send),
+    };
+    sig.generics
+        .make_where_clause()
+        .predicates
+        .push(bound);
+}
+
+/// moves the body of a rewritten method into the boxed future it returns
+fn rewrite_body(block: &mut syn::Block) {
+    *block = parse_quote!({
+        ::dyn_inventory_runtime::alloc::boxed::Box::pin(async move #block)
+    });
+}
+
+/// rewrites the async methods of a trait or impl so the trait stays dyn-compatible.
+pub fn expand(
+    args: DynAsyncArgs,
+    item: Item,
+) -> syn::Result<TokenStream> {
+    match item {
+        Item::Trait(mut item) => {
+            for item in &mut item.items {
+                if let TraitItem::Fn(method) = item
+                    && method.sig.asyncness.is_some()
+                {
+                    rewrite_signature(&mut method.sig, args.send);
+                    if let Some(block) = &mut method.default {
+                        // implementations only need the bound when they keep the default body
+                        if args.send {
+                            bound_receiver(&mut method.sig);
+                        }
+                        rewrite_body(block);
+                    }
+                }
+            }
+            Ok(item.into_token_stream())
+        },
+        Item::Impl(mut item) => {
+            for item in &mut item.items {
+                if let ImplItem::Fn(method) = item
+                    && method.sig.asyncness.is_some()
+                {
+                    rewrite_signature(&mut method.sig, args.send);
+                    rewrite_body(&mut method.block);
+                }
+            }
+            Ok(item.into_token_stream())
+        },
+        item => {
+            Err(syn::Error::new_spanned(
+                item,
+                "`#[dyn_async]` can only be applied to traits and impls",
+            ))
+        },
+    }
+}
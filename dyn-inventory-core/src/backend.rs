@@ -0,0 +1,113 @@
+use proc_macro2::TokenStream;
+use syn::Ident;
+
+/// how registrations are submitted at link time, and found again at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// `inventory::submit!`, collected by constructors which run before `main`
+    Inventory,
+    /// a `linkme` distributed slice, assembled by the linker
+    Linkme,
+}
+
+impl Default for Backend {
+    /// `inventory`, unless a registry sets `backend = linkme`
+    fn default() -> Self {
+        Self::Inventory
+    }
+}
+
+impl Backend {
+    /// parses the value of a `backend = ..` option
+    pub fn parse(value: &Ident) -> syn::Result<Self> {
+        match value.to_string().as_str() {
+            "inventory" => Ok(Self::Inventory),
+            "linkme" => Ok(Self::Linkme),
+            _ => {
+                Err(syn::Error::new(
+                    value.span(),
+                    "expected `inventory` or `linkme`",
+                ))
+            },
+        }
+    }
+
+    /// declares where the registrations of `struct_init` are collected.
+    ///
+    /// the distributed slice is named after the registration type. the static lives in the value
+    /// namespace and the struct in the type namespace, so importing the registration type for
+    /// `emit!` imports the slice too.
+    pub(crate) fn declare(
+        self,
+        struct_init: &Ident,
+    ) -> TokenStream {
+        match self {
+            Self::Inventory => {
+                quote::quote! {
+                    ::inventory::collect!{
+                        #struct_init
+                    }
+                }
+            },
+            Self::Linkme => {
+                quote::quote! {
+                    #[::linkme::distributed_slice]
+                    #[allow(non_upper_case_globals)]
+                    pub static #struct_init: [#struct_init];
+                }
+            },
+        }
+    }
+
+    /// every registration of `struct_init` submitted at link time
+    pub(crate) fn iter(
+        self,
+        struct_init: &Ident,
+    ) -> TokenStream {
+        match self {
+            Self::Inventory => quote::quote! { ::inventory::iter::<#struct_init>.into_iter() },
+            Self::Linkme => quote::quote! { #struct_init.iter() },
+        }
+    }
+
+    /// submits `init`, a registration of `struct_init`
+    pub(crate) fn submit(
+        self,
+        struct_init: &Ident,
+        init: TokenStream,
+    ) -> TokenStream {
+        let (expected, other) = match self {
+            Self::Inventory => ("inventory", "linkme"),
+            Self::Linkme => ("linkme", "inventory"),
+        };
+        let linkme = self == Self::Linkme;
+        let mismatch = format!(
+            "`{struct_init}` is collected with {other}, but this plugin was submitted with {expected}. pass `backend = {other}` to `emit!`"
+        );
+        let check = quote::quote! {
+            const _: () = ::core::assert!(#struct_init::__LINKME == #linkme, #mismatch);
+        };
+
+        match self {
+            Self::Inventory => {
+                quote::quote! {
+                    #check
+
+                    ::inventory::submit!{
+                        #init
+                    }
+                }
+            },
+            Self::Linkme => {
+                quote::quote! {
+                    const _: () = {
+                        #check
+
+                        #[::linkme::distributed_slice(#struct_init)]
+                        static REGISTRATION: #struct_init = #init;
+                    };
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use proc_macro2::TokenStream;
+use syn::Ident;
+
+use crate::args::Args;
+
+/// generates a `clap` argument and subcommand for each plugin, named by the `clap` field, and
+/// lookups of the plugins which were matched after parsing.
+///
+/// the collector only augments a `clap::Command` the application builds, so plugin crates add
+/// options or subcommands to a cli they don't own. with `help`, each plugin describes its argument
+/// or subcommand with that field.
+pub fn expand(
+    args: &Args,
+    name: &Ident,
+) -> TokenStream {
+    let strct = &args.struct_name;
+    let collector = args.derived_name("Collector");
+
+    let (arg_help, about) = match &args.help {
+        Some(help) => {
+            (
+                quote::quote! { .help(self.#help) },
+                quote::quote! { .about(self.#help) },
+            )
+        },
+        None => (quote::quote! {}, quote::quote! {}),
+    };
+
+    quote::quote! {
+        impl #strct {
+            /// this plugin as an option which takes a value, `--name <VALUE>`
+            pub fn arg(&self) -> ::clap::Arg {
+                ::clap::Arg::new(self.#name)
+                    .long(self.#name)
+                    #arg_help
+                    .action(::clap::ArgAction::Set)
+            }
+
+            /// this plugin as a subcommand, `name`
+            pub fn subcommand(&self) -> ::clap::Command {
+                ::clap::Command::new(self.#name) #about
+            }
+        }
+
+        impl #collector {
+            /// adds the option of every plugin to `command`
+            pub fn augment_args(&self, command: ::clap::Command) -> ::clap::Command {
+                command.args(self.plugins.iter().map(#strct::arg))
+            }
+
+            /// adds the subcommand of every plugin to `command`
+            pub fn augment_subcommands(&self, command: ::clap::Command) -> ::clap::Command {
+                command.subcommands(self.plugins.iter().map(#strct::subcommand))
+            }
+
+            /// the plugins whose option was passed, with the value passed, in registration order.
+            /// plugins without an option in the command `matches` was parsed by are skipped.
+            pub fn matched_args<'a>(
+                &'a self,
+                matches: &'a ::clap::ArgMatches,
+            ) -> impl ::core::iter::Iterator<Item = (&'a #strct, &'a str)> {
+                self.plugins.iter().filter_map(move |plugin| {
+                    matches
+                        .try_get_one::<::dyn_inventory_runtime::alloc::string::String>(plugin.#name)
+                        .ok()
+                        .flatten()
+                        .map(|value| (plugin, value.as_str()))
+                })
+            }
+
+            /// the plugin whose subcommand was passed, with the matches of the subcommand.
+            /// `matches` must be parsed by a command augmented with [`Self::augment_subcommands`].
+            pub fn matched_subcommand<'a>(
+                &'a self,
+                matches: &'a ::clap::ArgMatches,
+            ) -> ::core::option::Option<(&'a #strct, &'a ::clap::ArgMatches)> {
+                let (name, matches) = matches.subcommand()?;
+                self.plugins
+                    .iter()
+                    .find(|plugin| plugin.#name == name)
+                    .map(|plugin| (plugin, matches))
+            }
+        }
+    }
+}
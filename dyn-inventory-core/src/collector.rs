@@ -0,0 +1,546 @@
+use proc_macro2::TokenStream;
+
+use crate::args::Args;
+
+/// generates the collector, which instantiates registrations into plugins.
+///
+/// registrations pass through each enabled stage in turn: api version checks, priority ordering,
+/// dependency ordering, and finally instantiation. with `error`, registrations whose factory fails are reported
+/// alongside the plugins which loaded, and with `catch_unwind`, so are those whose factory panics.
+/// with `async_factory`, instantiation awaits each factory, and the blocking constructors drive it
+/// with [`block_on`](https://docs.rs/dyn-inventory-runtime).
+pub fn expand(args: &Args) -> TokenStream {
+    let strct = &args.struct_name;
+    let struct_init = &args.init_name;
+    let collector = args.derived_name("Collector");
+
+    let keyed = args.key.as_ref().map(|key| {
+        let instance = match &args.error {
+            Some(err) => quote::quote! { ::core::result::Result<#strct, #err> },
+            None => quote::quote! { #strct },
+        };
+        let spawn = if args.async_factory {
+            quote::quote! {
+                pub async fn spawn(&self, key: &str) -> ::core::option::Option<#instance> {
+                    match self.get(key) {
+                        ::core::option::Option::Some(plugin) => ::core::option::Option::Some(plugin.new_instance().await),
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    }
+                }
+            }
+        } else {
+            quote::quote! {
+                pub fn spawn(&self, key: &str) -> ::core::option::Option<#instance> {
+                    self.get(key).map(#strct::new_instance)
+                }
+            }
+        };
+
+        quote::quote! {
+            impl #strct {
+                pub fn key(&self) -> &'static str {
+                    self.#key
+                }
+            }
+
+            impl #collector {
+                pub fn get(&self, key: &str) -> ::core::option::Option<&#strct> {
+                    self.plugins.iter().find(|plugin| plugin.#key == key)
+                }
+
+                /// a new instance of the plugin registered under `key`, independent of the collected one
+                #spawn
+            }
+        }
+    });
+
+    let unwrap_built = |built: TokenStream| {
+        match &args.depends_on {
+            Some(_) => {
+                quote::quote! {
+                    match #built {
+                        ::core::result::Result::Ok(this) => this,
+                        ::core::result::Result::Err(err) => ::core::panic!("{err}"),
+                    }
+                }
+            },
+            None => built,
+        }
+    };
+
+    let (build_ty, fallible, ordering, order) = match (&args.key, &args.depends_on) {
+        (Some(key), Some(depends_on)) => {
+            let err = args.derived_name("DependencyError");
+            (
+                quote::quote! { ::core::result::Result<Self, #err> },
+                true,
+                Some(crate::deps::expand(args, key, depends_on)),
+                quote::quote! {
+                    let inits = Self::order(inits)?;
+                },
+            )
+        },
+        _ => (quote::quote! { Self }, false, None, quote::quote! {}),
+    };
+
+    let new_with = unwrap_built(quote::quote! {
+        Self::build(Self::registered(), with)
+    });
+
+    let (api_field, api_check, api_init, api_versions) = match &args.api_version {
+        Some(_) => {
+            (
+                quote::quote! {
+                    /// registrations built against an incompatible api version, which were not instantiated
+                    pub incompatible: ::dyn_inventory_runtime::alloc::vec::Vec<(#struct_init, ::dyn_inventory_runtime::ApiMismatch)>,
+                },
+                quote::quote! {
+                    let (inits, incompatible) = Self::check_api_versions(inits);
+                },
+                quote::quote! { incompatible, },
+                Some(crate::version::expand(args)),
+            )
+        },
+        None => (quote::quote! {}, quote::quote! {}, quote::quote! {}, None),
+    };
+
+    let (failed_field, failed_decl, failed_init) = match &args.error {
+        Some(err) => {
+            (
+                quote::quote! {
+                    /// registrations whose factory returned an error, with that error
+                    pub failed: ::dyn_inventory_runtime::alloc::vec::Vec<(#struct_init, #err)>,
+                },
+                quote::quote! {
+                    let mut failed = ::dyn_inventory_runtime::alloc::vec::Vec::new();
+                },
+                quote::quote! { failed, },
+            )
+        },
+        None => (quote::quote! {}, quote::quote! {}, quote::quote! {}),
+    };
+
+    let (panicked_field, panicked_decl, panicked_init) = if args.catch_unwind {
+        (
+            quote::quote! {
+                /// registrations whose factory panicked, with the panic
+                pub panicked: ::dyn_inventory_runtime::alloc::vec::Vec<(#struct_init, ::dyn_inventory_runtime::Panic)>,
+            },
+            quote::quote! {
+                let mut panicked = ::dyn_inventory_runtime::alloc::vec::Vec::new();
+            },
+            quote::quote! { panicked, },
+        )
+    } else {
+        (quote::quote! {}, quote::quote! {}, quote::quote! {})
+    };
+
+    let (misconfigured_field, misconfigured_decl, misconfigured_init) = if args.config {
+        (
+            quote::quote! {
+                /// registrations whose section did not match the configuration their factory takes, with
+                /// what was wrong with it
+                pub misconfigured: ::dyn_inventory_runtime::alloc::vec::Vec<(#struct_init, ::dyn_inventory_runtime::config::ConfigError)>,
+            },
+            quote::quote! {
+                let mut misconfigured = ::dyn_inventory_runtime::alloc::vec::Vec::new();
+            },
+            quote::quote! { misconfigured, },
+        )
+    } else {
+        (quote::quote! {}, quote::quote! {}, quote::quote! {})
+    };
+
+    // the registration is only cloned when a failure needs to be reported alongside it
+    let reported = args.error.is_some() || args.catch_unwind || args.config;
+    let init = if reported {
+        quote::quote! { plugin.clone() }
+    } else {
+        quote::quote! { plugin }
+    };
+    // configured registrations are instantiated by what `configure` returns
+    let configure = args
+        .key
+        .as_ref()
+        .filter(|_| args.config)
+        .map(|key| {
+            quote::quote! {
+                let configured = match #init.configure(config.section(plugin.#key)) {
+                    ::core::result::Result::Ok(configured) => configured,
+                    ::core::result::Result::Err(err) => {
+                        construction.finish(::dyn_inventory_runtime::trace::Outcome::Misconfigured);
+                        misconfigured.push((plugin, err));
+                        continue;
+                    },
+                };
+            }
+        });
+    let (instantiate_async, instantiate_fallible, instantiate_infallible) = if args.config {
+        (
+            quote::quote! { configured },
+            quote::quote! { configured() },
+            quote::quote! { configured() },
+        )
+    } else {
+        (
+            quote::quote! { #init.instantiate() },
+            quote::quote! { #init.instantiate() },
+            quote::quote! { <#strct as ::core::convert::From<#struct_init>>::from(#init) },
+        )
+    };
+    // factories run within the span of their construction
+    let instantiated = match (args.async_factory, args.catch_unwind) {
+        (true, true) => {
+            quote::quote! {
+                construction.instrument(::dyn_inventory_runtime::CatchUnwind::new(::dyn_inventory_runtime::alloc::boxed::Box::pin(
+                    #instantiate_async,
+                )))
+                .await
+            }
+        },
+        (true, false) => quote::quote! { construction.instrument(#instantiate_async).await },
+        (false, true) if args.error.is_some() => {
+            quote::quote! {
+                construction.in_scope(|| ::dyn_inventory_runtime::catch_unwind(|| #instantiate_fallible))
+            }
+        },
+        (false, true) => {
+            quote::quote! {
+                construction.in_scope(|| ::dyn_inventory_runtime::catch_unwind(|| #instantiate_infallible))
+            }
+        },
+        (false, false) if args.error.is_some() => {
+            quote::quote! { construction.in_scope(|| #instantiate_fallible) }
+        },
+        (false, false) => {
+            quote::quote! { construction.in_scope(|| #instantiate_infallible) }
+        },
+    };
+    let failed = quote::quote! {
+        construction.finish(::dyn_inventory_runtime::trace::Outcome::Failed);
+    };
+    let panicked = quote::quote! {
+        construction.finish(::dyn_inventory_runtime::trace::Outcome::Panicked);
+    };
+    let instantiate = match (&args.error, args.catch_unwind) {
+        (Some(_), true) => {
+            quote::quote! {
+                let mut plugin = match #instantiated {
+                    ::core::result::Result::Ok(::core::result::Result::Ok(instance)) => instance,
+                    ::core::result::Result::Ok(::core::result::Result::Err(err)) => {
+                        #failed
+                        failed.push((plugin, err));
+                        continue;
+                    },
+                    ::core::result::Result::Err(panic) => {
+                        #panicked
+                        panicked.push((plugin, panic));
+                        continue;
+                    },
+                };
+            }
+        },
+        (Some(_), false) => {
+            quote::quote! {
+                let mut plugin = match #instantiated {
+                    ::core::result::Result::Ok(instance) => instance,
+                    ::core::result::Result::Err(err) => {
+                        #failed
+                        failed.push((plugin, err));
+                        continue;
+                    },
+                };
+            }
+        },
+        (None, true) => {
+            quote::quote! {
+                let mut plugin = match #instantiated {
+                    ::core::result::Result::Ok(instance) => instance,
+                    ::core::result::Result::Err(panic) => {
+                        #panicked
+                        panicked.push((plugin, panic));
+                        continue;
+                    },
+                };
+            }
+        },
+        (None, false) => {
+            quote::quote! {
+                let mut plugin = #instantiated;
+            }
+        },
+    };
+
+    let (library_field, library_init, dylib) = if args.dylib {
+        (
+            quote::quote! {
+                /// the shared libraries plugins were loaded from. they stay loaded for the rest of the
+                /// process, so that the plugins, and everything taken from them, remain valid
+                pub libraries: ::dyn_inventory_runtime::alloc::vec::Vec<::dyn_inventory_runtime::Library>,
+            },
+            quote::quote! { libraries: ::dyn_inventory_runtime::alloc::vec::Vec::new(), },
+            Some(crate::dylib::expand(args)),
+        )
+    } else {
+        (quote::quote! {}, quote::quote! {}, None)
+    };
+
+    // with tags, the plugins are indexed by position, so they are only changed through the
+    // collector
+    let (plugins_vis, tags_field, tags_init, tags) = match &args.tags {
+        Some(tags) => {
+            (
+                quote::quote! {},
+                quote::quote! {
+                    tag_index: ::dyn_inventory_runtime::alloc::collections::BTreeMap<
+                        &'static str,
+                        ::dyn_inventory_runtime::alloc::vec::Vec<usize>,
+                    >,
+                },
+                quote::quote! { tag_index: ::dyn_inventory_runtime::alloc::collections::BTreeMap::new(), },
+                Some(crate::tags::expand(args, tags)),
+            )
+        },
+        None => {
+            (
+                quote::quote! { pub },
+                quote::quote! {},
+                quote::quote! {},
+                None,
+            )
+        },
+    };
+
+    let built = quote::quote! {
+        Self { plugins, #api_init #failed_init #panicked_init #misconfigured_init #tags_init #library_init }
+    };
+    let built = if args.tags.is_some() {
+        quote::quote! {
+            {
+                let mut this = #built;
+                this.index_tags();
+                this
+            }
+        }
+    } else {
+        built
+    };
+    let built = if fallible {
+        quote::quote! { ::core::result::Result::Ok(#built) }
+    } else {
+        built
+    };
+
+    let registry_name = strct.to_string();
+    let key = match &args.key {
+        Some(key) => quote::quote! { ::core::option::Option::Some(plugin.#key) },
+        None => quote::quote! { ::core::option::Option::None },
+    };
+    // a stable sort, so that dependency ordering breaks its ties by priority, then registration order
+    let prioritize = args.priority.as_ref().map(|priority| {
+        quote::quote! {
+            let mut inits = inits;
+            inits.sort_by(|a, b| ::core::cmp::Ord::cmp(&a.#priority, &b.#priority));
+        }
+    });
+
+    let collect = quote::quote! {
+        let collection = ::dyn_inventory_runtime::trace::Collection::start(#registry_name, inits.len());
+        #api_check
+        #prioritize
+        #order
+        #failed_decl
+        #panicked_decl
+        #misconfigured_decl
+        let mut plugins = ::dyn_inventory_runtime::alloc::vec::Vec::new();
+        for plugin in inits {
+            let construction = collection.construct(#key, &plugin.__source);
+            #configure
+            #instantiate
+            construction.finish(::dyn_inventory_runtime::trace::Outcome::Loaded);
+            with(&mut plugin);
+            plugins.push(plugin);
+        }
+        collection.finish(plugins.len());
+        #built
+    };
+
+    // configured registries collect without a config through `build` as well, configuring every
+    // plugin from an empty section
+    let (configured_build, configured_build_async) = if args.config {
+        (
+            Some(quote::quote! {
+                fn build<F: ::core::ops::Fn(&mut #strct)>(inits: ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>, with: F) -> #build_ty {
+                    Self::build_configured(inits, &::core::default::Default::default(), with)
+                }
+            }),
+            Some(quote::quote! {
+                async fn build_async<F: ::core::ops::Fn(&mut #strct)>(inits: ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>, with: F) -> #build_ty {
+                    Self::build_configured_async(inits, &::core::default::Default::default(), with).await
+                }
+            }),
+        )
+    } else {
+        (None, None)
+    };
+
+    let build = match (args.async_factory, configured_build_async) {
+        (true, configured) => {
+            let new_async_with = unwrap_built(quote::quote! {
+                Self::build_async(Self::registered(), with).await
+            });
+            let build_async = match configured {
+                Some(configured) => {
+                    quote::quote! {
+                        #configured
+
+                        async fn build_configured_async<F: ::core::ops::Fn(&mut #strct)>(
+                            inits: ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>,
+                            config: &::dyn_inventory_runtime::config::Config,
+                            with: F,
+                        ) -> #build_ty {
+                            #collect
+                        }
+                    }
+                },
+                None => {
+                    quote::quote! {
+                        async fn build_async<F: ::core::ops::Fn(&mut #strct)>(inits: ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>, with: F) -> #build_ty {
+                            #collect
+                        }
+                    }
+                },
+            };
+            quote::quote! {
+                fn build<F: ::core::ops::Fn(&mut #strct)>(inits: ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>, with: F) -> #build_ty {
+                    ::dyn_inventory_runtime::block_on(Self::build_async(inits, with))
+                }
+
+                #build_async
+
+                /// collects every plugin, awaiting their factories in turn
+                pub async fn new_async() -> Self {
+                    Self::new_async_with(|_| {}).await
+                }
+
+                pub async fn new_async_with<F: ::core::ops::Fn(&mut #strct)>(with: F) -> Self {
+                    #new_async_with
+                }
+            }
+        },
+        (false, _) => {
+            match configured_build {
+                Some(configured) => {
+                    quote::quote! {
+                        #configured
+
+                        fn build_configured<F: ::core::ops::Fn(&mut #strct)>(
+                            inits: ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>,
+                            config: &::dyn_inventory_runtime::config::Config,
+                            with: F,
+                        ) -> #build_ty {
+                            #collect
+                        }
+                    }
+                },
+                None => {
+                    quote::quote! {
+                        fn build<F: ::core::ops::Fn(&mut #strct)>(inits: ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>, with: F) -> #build_ty {
+                            #collect
+                        }
+                    }
+                },
+            }
+        },
+    };
+
+    let config = args
+        .config
+        .then(|| crate::config::expand(args, &build_ty));
+
+    let selection = args
+        .key
+        .as_ref()
+        .map(|key| crate::select::expand(args, key));
+
+    let registry = args
+        .key
+        .as_ref()
+        .map(|key| crate::registry::expand(args, key));
+
+    let downcast = args
+        .downcast
+        .then(|| crate::downcast::expand(args));
+
+    let clap = args
+        .clap
+        .as_ref()
+        .map(|name| crate::clap::expand(args, name));
+
+    let lifecycle = args
+        .lifecycle
+        .then(|| crate::lifecycle::expand(args));
+
+    quote::quote! {
+        pub struct #collector {
+            #plugins_vis plugins: ::dyn_inventory_runtime::alloc::vec::Vec<#strct>,
+            #api_field
+            #failed_field
+            #panicked_field
+            #misconfigured_field
+            #tags_field
+            #library_field
+        }
+
+        impl #collector {
+            pub fn new() -> Self {
+                Self::new_with(|_| {})
+            }
+
+            pub fn new_with<F: ::core::ops::Fn(&mut #strct)>(with: F) -> Self {
+                #new_with
+            }
+
+            /// collects the plugins of `inits` instead of the registered plugins, such as registrations
+            /// built with `mock_plugin!`.
+            pub fn from_inits(inits: impl ::core::iter::IntoIterator<Item = #struct_init>) -> #build_ty {
+                Self::from_inits_with(inits, |_| {})
+            }
+
+            pub fn from_inits_with<F: ::core::ops::Fn(&mut #strct)>(
+                inits: impl ::core::iter::IntoIterator<Item = #struct_init>,
+                with: F,
+            ) -> #build_ty {
+                Self::build(inits.into_iter().collect(), with)
+            }
+
+            fn registered() -> ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init> {
+                #struct_init::iter().cloned().collect()
+            }
+
+            #build
+        }
+
+        #keyed
+
+        #ordering
+
+        #api_versions
+
+        #selection
+
+        #registry
+
+        #tags
+
+        #downcast
+
+        #clap
+
+        #config
+
+        #lifecycle
+
+        #dylib
+    }
+}
@@ -0,0 +1,98 @@
+use proc_macro2::TokenStream;
+
+use crate::args::Args;
+
+/// generates the constructors of a configured collector, which configure each plugin by the
+/// section of a `Config` under its key.
+///
+/// registrations whose section does not deserialize into the configuration their factory takes are
+/// not instantiated, and are reported in `StructNameCollector::misconfigured` instead. a config which
+/// is not a table, or has a section no plugin is registered under, fails the whole collection.
+pub fn expand(
+    args: &Args,
+    build_ty: &TokenStream,
+) -> TokenStream {
+    let strct = &args.struct_name;
+    let struct_init = &args.init_name;
+    let collector = args.derived_name("Collector");
+    let key = args
+        .key
+        .as_ref()
+        .expect("`config` requires a key, which is checked while parsing");
+
+    let config_ty = quote::quote! {
+        impl ::core::convert::TryInto<
+            ::dyn_inventory_runtime::config::Config,
+            Error: ::core::convert::Into<::dyn_inventory_runtime::config::ConfigError>,
+        >
+    };
+    let configured_ty = quote::quote! {
+        ::core::result::Result<#build_ty, ::dyn_inventory_runtime::config::ConfigError>
+    };
+
+    let from_config_with = if args.async_factory {
+        quote::quote! {
+            ::dyn_inventory_runtime::block_on(Self::from_config_async_with(config, with))
+        }
+    } else {
+        quote::quote! {
+            let (inits, config) = Self::configured(config)?;
+            ::core::result::Result::Ok(Self::build_configured(inits, &config, with))
+        }
+    };
+
+    let from_config_async = args.async_factory.then(|| {
+        quote::quote! {
+            /// collects every plugin, each configured by the section of `config` under its key,
+            /// awaiting their factories in turn
+            pub async fn from_config_async(config: #config_ty) -> #configured_ty {
+                Self::from_config_async_with(config, |_| {}).await
+            }
+
+            pub async fn from_config_async_with<F: ::core::ops::Fn(&mut #strct)>(
+                config: #config_ty,
+                with: F,
+            ) -> #configured_ty {
+                let (inits, config) = Self::configured(config)?;
+                ::core::result::Result::Ok(Self::build_configured_async(inits, &config, with).await)
+            }
+        }
+    });
+
+    quote::quote! {
+        impl #collector {
+            /// converts `config`, and checks that each of its sections configures a registered plugin
+            fn configured(
+                config: #config_ty,
+            ) -> ::core::result::Result<
+                (
+                    ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>,
+                    ::dyn_inventory_runtime::config::Config,
+                ),
+                ::dyn_inventory_runtime::config::ConfigError,
+            > {
+                let config: ::dyn_inventory_runtime::config::Config =
+                    ::core::convert::TryInto::try_into(config).map_err(::core::convert::Into::into)?;
+                let inits = Self::registered();
+                config.check(inits.iter().map(|init| init.#key))?;
+                ::core::result::Result::Ok((inits, config))
+            }
+
+            /// collects every plugin, each configured by the section of `config` under its key.
+            /// plugins without a section are configured from an empty table. fails when `config` is
+            /// not a table of sections, or has a section no plugin is registered under.
+            pub fn from_config(config: #config_ty) -> #configured_ty {
+                Self::from_config_with(config, |_| {})
+            }
+
+            pub fn from_config_with<F: ::core::ops::Fn(&mut #strct)>(
+                config: #config_ty,
+                with: F,
+            ) -> #configured_ty {
+                #from_config_with
+            }
+
+            #from_config_async
+        }
+    }
+}
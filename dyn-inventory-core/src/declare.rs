@@ -0,0 +1,293 @@
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use syn::{Expr, Ident, Token, Visibility, braced, parse::Parse, punctuated::Punctuated};
+
+use crate::{backend::Backend, init};
+
+/// a metadata field of a registration, `name = value`.
+pub struct FieldValue {
+    /// the name of the field
+    pub name: Ident,
+    #[allow(unused)]
+    eq: Token![=],
+    /// the expression the field is set to
+    pub value: Expr,
+}
+
+impl Parse for FieldValue {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            name: input.parse()?,
+            eq: input.parse()?,
+            value: input.parse()?,
+        })
+    }
+}
+
+mod kw {
+    syn::custom_keyword!(factory);
+    syn::custom_keyword!(handle);
+    syn::custom_keyword!(backend);
+}
+
+#[allow(non_camel_case_types)]
+enum EmitOpts {
+    factory { value: Expr },
+    backend { value: Ident },
+}
+
+impl Parse for EmitOpts {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        kws! {
+            input & [factory, backend]
+        }
+    }
+}
+
+/// the arguments to `emit!`, which submits a plugin to a registry.
+///
+/// `[vis] Handle Trait as Plugin { field = value, .. }`, followed by `; factory = .., backend = ..`
+/// when either is set.
+pub struct EmitArgs {
+    /// the visibility of the unit struct declared when there is no factory
+    pub vis: Visibility,
+
+    /// the type of the handle
+    pub handle_name: Ident,
+
+    /// the trait the handle implements. it is not checked by `emit!`, the registry checks it.
+    pub trait_name: Ident,
+
+    #[allow(unused)]
+    _as: syn::token::As,
+
+    /// the registry struct the plugin is submitted to
+    pub plugin_name: Ident,
+
+    #[allow(unused)]
+    brace: syn::token::Brace,
+
+    /// the metadata of the plugin
+    pub fields: Punctuated<FieldValue, Token![,]>,
+
+    #[allow(unused)]
+    term: Option<Token![;]>,
+
+    opts: Punctuated<EmitOpts, Token![,]>,
+
+    /// builds the handle, instead of the unit struct declared by `emit!`
+    pub factory: Option<Expr>,
+
+    /// must match the backend of the registry
+    pub backend: Backend,
+}
+
+impl Parse for EmitArgs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let braced;
+
+        let mut this = Self {
+            vis: input.parse()?,
+            handle_name: input.parse()?,
+            trait_name: input.parse()?,
+            _as: input.parse()?,
+            plugin_name: input.parse()?,
+            brace: braced!(braced in input),
+            fields: braced.parse_terminated(FieldValue::parse, Token![,])?,
+            term: None,
+            opts: Punctuated::new(),
+            factory: None,
+            backend: Backend::default(),
+        };
+
+        if !input.is_empty() {
+            this.term = Some(input.parse()?);
+            this.opts = input.parse_terminated(EmitOpts::parse, Token![,])?;
+        }
+
+        for opt in &this.opts {
+            match opt {
+                EmitOpts::factory { value } => {
+                    this.factory = Some(value.clone());
+                },
+                EmitOpts::backend { value } => {
+                    this.backend = Backend::parse(value)?;
+                },
+            }
+        }
+
+        Ok(this)
+    }
+}
+
+impl ToTokens for EmitArgs {
+    fn to_tokens(
+        &self,
+        tokens: &mut proc_macro2::TokenStream,
+    ) {
+        let plugin = &self.plugin_name;
+        let handle_name = &self.handle_name;
+        let vis = &self.vis;
+
+        let fields_as_expr_assign: TokenStream = self
+            .fields
+            .iter()
+            .map(|field| {
+                let name = &field.name;
+                let expr = &field.value;
+                quote::quote! {
+                    #name: #expr,
+                }
+            })
+            .collect();
+
+        let struct_init = init(plugin);
+
+        // the handle type is declared by the caller when it is built by a factory
+        let (handle_def, get) = match &self.factory {
+            Some(factory) => {
+                (
+                    quote::quote! {},
+                    quote::quote! {
+                        ::dyn_inventory_runtime::Constructor::new(|| #struct_init::__factory(#factory))
+                    },
+                )
+            },
+            None => {
+                (
+                    quote::quote! {
+                        #vis struct #handle_name;
+                    },
+                    quote::quote! {
+                        ::dyn_inventory_runtime::Constructor::new(|| {
+                            #struct_init::__handle::<#handle_name>(#handle_name)
+                        })
+                    },
+                )
+            },
+        };
+
+        let submit = self.backend.submit(
+            &struct_init,
+            quote::quote! {
+                #struct_init{
+                    #fields_as_expr_assign
+                    __get: #get,
+                    __source: ::dyn_inventory_runtime::source!(),
+                    __api_version: #struct_init::API_VERSION,
+                }
+            },
+        );
+
+        tokens.extend(quote::quote! {
+            #handle_def
+
+            #submit
+        });
+    }
+}
+
+#[allow(non_camel_case_types)]
+enum MockOpts {
+    handle { value: Expr },
+    factory { value: Expr },
+}
+
+impl Parse for MockOpts {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        kws! {
+            input & [handle, factory]
+        }
+    }
+}
+
+/// the arguments to `mock_plugin!`, which builds a registration without submitting it.
+pub struct MockArgs {
+    /// the registry struct the plugin is built for
+    pub plugin_name: Ident,
+
+    #[allow(unused)]
+    brace: syn::token::Brace,
+
+    /// the metadata of the plugin
+    pub fields: Punctuated<FieldValue, Token![,]>,
+
+    #[allow(unused)]
+    term: Token![;],
+
+    /// the handle, or the factory which builds it
+    get: MockOpts,
+}
+
+impl Parse for MockArgs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let braced;
+
+        let this = Self {
+            plugin_name: input.parse()?,
+            brace: braced!(braced in input),
+            fields: braced.parse_terminated(FieldValue::parse, Token![,])?,
+            term: input.parse()?,
+            get: input.parse()?,
+        };
+        let _: Option<Token![,]> = input.parse()?;
+        if !input.is_empty() {
+            return Err(syn::Error::new(
+                input.span(),
+                "expected exactly one of `handle = ..` or `factory = ..`",
+            ));
+        }
+
+        Ok(this)
+    }
+}
+
+impl ToTokens for MockArgs {
+    fn to_tokens(
+        &self,
+        tokens: &mut proc_macro2::TokenStream,
+    ) {
+        let struct_init = init(&self.plugin_name);
+
+        let fields_as_expr_assign: TokenStream = self
+            .fields
+            .iter()
+            .map(|field| {
+                let name = &field.name;
+                let expr = &field.value;
+                quote::quote! {
+                    #name: #expr,
+                }
+            })
+            .collect();
+
+        let get = match &self.get {
+            MockOpts::handle { value } => {
+                // evaluated each time the factory runs, so it may capture state it clones
+                quote::quote! {
+                    ::dyn_inventory_runtime::Constructor::shared(move || #struct_init::__handle(#value))
+                }
+            },
+            MockOpts::factory { value } => {
+                // the factory is consumed by each run, so every run gets a clone of it
+                quote::quote! {
+                    {
+                        let factory = #value;
+                        ::dyn_inventory_runtime::Constructor::shared(move || {
+                            #struct_init::__factory(::core::clone::Clone::clone(&factory))
+                        })
+                    }
+                }
+            },
+        };
+
+        tokens.extend(quote::quote! {
+            #struct_init {
+                #fields_as_expr_assign
+                __get: #get,
+                __source: ::dyn_inventory_runtime::source!(),
+                __api_version: #struct_init::API_VERSION,
+            }
+        });
+    }
+}
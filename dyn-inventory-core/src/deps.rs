@@ -0,0 +1,133 @@
+use proc_macro2::TokenStream;
+use syn::Ident;
+
+use crate::args::Args;
+
+/// generates the dependency error type and the topological ordering of registrations.
+///
+/// plugins are ordered so that every plugin comes after the plugins named in its `depends_on`
+/// field. ties are broken by registration order.
+pub fn expand(
+    args: &Args,
+    key: &Ident,
+    depends_on: &Ident,
+) -> TokenStream {
+    let strct = &args.struct_name;
+    let struct_init = &args.init_name;
+    let collector = args.derived_name("Collector");
+    let err = args.derived_name("DependencyError");
+
+    quote::quote! {
+        #[derive(::core::fmt::Debug, ::core::clone::Clone, ::core::cmp::PartialEq, ::core::cmp::Eq)]
+        pub enum #err {
+            /// more than one plugin was registered under the same key
+            Duplicate {
+                plugin: &'static str,
+                first: ::dyn_inventory_runtime::Source,
+                second: ::dyn_inventory_runtime::Source,
+            },
+            /// `plugin` depends on `dependency`, which is not registered
+            Missing {
+                plugin: &'static str,
+                dependency: &'static str,
+            },
+            /// the plugins form a dependency cycle, in order. the last plugin depends on the first.
+            Cycle { plugins: ::dyn_inventory_runtime::alloc::vec::Vec<&'static str> },
+        }
+
+        impl ::core::fmt::Display for #err {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    Self::Duplicate { plugin, first, second } => {
+                        ::core::write!(f, "plugin `{plugin}` is registered more than once, by {first} and {second}")
+                    },
+                    Self::Missing { plugin, dependency } => {
+                        ::core::write!(f, "plugin `{plugin}` depends on `{dependency}`, which is not registered")
+                    },
+                    Self::Cycle { plugins } => {
+                        ::core::write!(f, "dependency cycle: ")?;
+                        for plugin in plugins {
+                            ::core::write!(f, "`{plugin}` -> ")?;
+                        }
+                        match plugins.first() {
+                            ::core::option::Option::Some(first) => ::core::write!(f, "`{first}`"),
+                            ::core::option::Option::None => ::core::result::Result::Ok(()),
+                        }
+                    },
+                }
+            }
+        }
+
+        impl ::core::error::Error for #err {}
+
+        impl #collector {
+            fn order(inits: ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>) -> ::core::result::Result<::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>, #err> {
+                fn visit(
+                    idx: usize,
+                    inits: &[#struct_init],
+                    index: &::dyn_inventory_runtime::alloc::collections::BTreeMap<&'static str, usize>,
+                    state: &mut [u8],
+                    path: &mut ::dyn_inventory_runtime::alloc::vec::Vec<usize>,
+                    order: &mut ::dyn_inventory_runtime::alloc::vec::Vec<usize>,
+                ) -> ::core::result::Result<(), #err> {
+                    match state[idx] {
+                        2 => return ::core::result::Result::Ok(()),
+                        1 => {
+                            let start = path.iter().position(|p| *p == idx).unwrap_or_default();
+                            return ::core::result::Result::Err(#err::Cycle {
+                                plugins: path[start..].iter().map(|p| inits[*p].#key).collect(),
+                            });
+                        },
+                        _ => {},
+                    }
+
+                    state[idx] = 1;
+                    path.push(idx);
+                    for dependency in inits[idx].#depends_on {
+                        match index.get(dependency) {
+                            ::core::option::Option::Some(dep) => visit(*dep, inits, index, state, path, order)?,
+                            ::core::option::Option::None => {
+                                return ::core::result::Result::Err(#err::Missing {
+                                    plugin: inits[idx].#key,
+                                    dependency,
+                                });
+                            },
+                        }
+                    }
+                    path.pop();
+                    state[idx] = 2;
+                    order.push(idx);
+                    ::core::result::Result::Ok(())
+                }
+
+                let mut index = ::dyn_inventory_runtime::alloc::collections::BTreeMap::new();
+                for (idx, init) in inits.iter().enumerate() {
+                    if let ::core::option::Option::Some(first) = index.insert(init.#key, idx) {
+                        return ::core::result::Result::Err(#err::Duplicate {
+                            plugin: init.#key,
+                            first: inits[first].__source,
+                            second: init.__source,
+                        });
+                    }
+                }
+
+                let mut state = ::dyn_inventory_runtime::alloc::vec![0u8; inits.len()];
+                let mut path = ::dyn_inventory_runtime::alloc::vec::Vec::new();
+                let mut order = ::dyn_inventory_runtime::alloc::vec::Vec::with_capacity(inits.len());
+                for idx in 0..inits.len() {
+                    visit(idx, &inits, &index, &mut state, &mut path, &mut order)?;
+                }
+
+                ::core::result::Result::Ok(order.into_iter().map(|idx| inits[idx].clone()).collect())
+            }
+
+            pub fn try_new() -> ::core::result::Result<Self, #err> {
+                Self::try_new_with(|_| {})
+            }
+
+            pub fn try_new_with<F: ::core::ops::Fn(&mut #strct)>(with: F) -> ::core::result::Result<Self, #err> {
+                Self::build(Self::registered(), with)
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use proc_macro2::TokenStream;
+
+use crate::args::Args;
+
+/// generates access to the concrete type behind each handle.
+///
+/// the trait must declare `Any` as a supertrait, so that `&dyn Trait` can be upcast to `&dyn Any`.
+pub fn expand(args: &Args) -> TokenStream {
+    let strct = &args.struct_name;
+    let collector = args.derived_name("Collector");
+    let handle = &args.handle_field().name;
+
+    quote::quote! {
+        impl #strct {
+            /// the handle as its concrete type, if it is a `T`
+            pub fn downcast_ref<T: ::core::any::Any>(&self) -> ::core::option::Option<&T> {
+                let handle: &dyn ::core::any::Any = &*self.#handle;
+                handle.downcast_ref::<T>()
+            }
+
+            /// whether the handle is a `T`
+            pub fn is<T: ::core::any::Any>(&self) -> bool {
+                self.downcast_ref::<T>().is_some()
+            }
+        }
+
+        impl #collector {
+            /// the first plugin whose handle is a `T`
+            pub fn find_by_type<T: ::core::any::Any>(&self) -> ::core::option::Option<&#strct> {
+                self.plugins.iter().find(|plugin| plugin.is::<T>())
+            }
+        }
+    }
+}
@@ -0,0 +1,441 @@
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::{Ident, parse::Parse};
+
+use crate::args::Args;
+
+/// the symbol a shared library exports the registration table of `strct` under.
+fn symbol(strct: &Ident) -> Ident {
+    Ident::new(&format!("__dyn_inventory_{strct}"), Span::call_site())
+}
+
+/// generates the loading of registrations from shared libraries built with `export!`.
+///
+/// a library exports a [`Table`](https://docs.rs/dyn-inventory-runtime) of its registrations,
+/// which the host reads once it has checked that both were built against the same registry, by the
+/// same compiler. registrations point into the library as `'static`, so a library whose
+/// registrations were read is never unloaded.
+pub fn expand(args: &Args) -> TokenStream {
+    let strct = &args.struct_name;
+    let struct_init = &args.init_name;
+    let collector = args.derived_name("Collector");
+    let err = args.derived_name("LoadError");
+    let reloader = args.derived_name("Reloader");
+    let safety = format!("see [`{collector}::load`].");
+    let symbol = symbol(strct).to_string();
+    let registry = format!("::{strct}");
+
+    let (dependency_variant, dependency_display, dependency_from, built) = match &args.depends_on {
+        Some(_) => {
+            let dependency_err = args.derived_name("DependencyError");
+            (
+                quote::quote! {
+                    /// the plugins of the library could not be ordered by their dependencies
+                    Dependency(#dependency_err),
+                },
+                quote::quote! {
+                    Self::Dependency(err) => ::core::write!(f, "{err}"),
+                },
+                quote::quote! {
+                    impl ::core::convert::From<#dependency_err> for #err {
+                        fn from(err: #dependency_err) -> Self {
+                            Self::Dependency(err)
+                        }
+                    }
+                },
+                quote::quote! { ? },
+            )
+        },
+        None => {
+            (
+                quote::quote! {},
+                quote::quote! {},
+                quote::quote! {},
+                quote::quote! {},
+            )
+        },
+    };
+
+    let merge = [
+        args.api_version
+            .as_ref()
+            .map(|_| quote::quote! { incompatible }),
+        args.error
+            .as_ref()
+            .map(|_| quote::quote! { failed }),
+        args.catch_unwind
+            .then(|| quote::quote! { panicked }),
+        args.config
+            .then(|| quote::quote! { misconfigured }),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|field| {
+        quote::quote! {
+            self.#field.extend(loaded.#field);
+        }
+    });
+
+    let reindex = args.tags.as_ref().map(|_| {
+        quote::quote! {
+            self.index_tags();
+        }
+    });
+
+    quote::quote! {
+        impl #struct_init {
+            #[doc(hidden)]
+            pub const __REGISTRY: &'static str = ::core::concat!(
+                ::core::env!("CARGO_PKG_NAME"),
+                " ",
+                ::core::module_path!(),
+                #registry,
+            );
+
+            #[doc(hidden)]
+            pub fn __export() -> ::dyn_inventory_runtime::Table {
+                // the library also links the crate declaring the registry, whose own plugins the
+                // host links too, so those are left out. the plugins of every other crate bundled
+                // into the library are exported
+                static ENTRIES: ::dyn_inventory_runtime::std::sync::OnceLock<
+                    ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>,
+                > = ::dyn_inventory_runtime::std::sync::OnceLock::new();
+                let entries = ENTRIES.get_or_init(|| {
+                    #struct_init::iter()
+                        .filter(|init| init.__source.package != ::core::env!("CARGO_PKG_NAME"))
+                        .cloned()
+                        .collect()
+                });
+                ::dyn_inventory_runtime::Table::new(Self::__REGISTRY, Self::API_VERSION, entries)
+            }
+        }
+
+        #[derive(::core::fmt::Debug)]
+        pub enum #err {
+            /// the library could not be opened, or was not built for this registry
+            Library(::dyn_inventory_runtime::LoadError),
+            #dependency_variant
+        }
+
+        impl ::core::fmt::Display for #err {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    Self::Library(err) => ::core::write!(f, "{err}"),
+                    #dependency_display
+                }
+            }
+        }
+
+        impl ::core::error::Error for #err {}
+
+        impl ::core::convert::From<::dyn_inventory_runtime::LoadError> for #err {
+            fn from(err: ::dyn_inventory_runtime::LoadError) -> Self {
+                Self::Library(err)
+            }
+        }
+
+        #dependency_from
+
+        impl #collector {
+            /// loads the plugins exported by the shared library at `path` with `dyn_inventory::export!`,
+            /// adding them to this collection.
+            ///
+            /// the `&'static` fields, sources and code of the loaded plugins point into the library, so
+            /// once its registrations are read, the library stays loaded for the rest of the process,
+            /// and they remain valid once the plugins, this collector, and every [`Library`] handle are
+            /// dropped. loading a library many times keeps every copy loaded.
+            ///
+            /// [`Library`]: ::dyn_inventory_runtime::Library
+            ///
+            /// # Safety
+            ///
+            /// - opening a library runs its initializers, which may do anything
+            /// - the library must export this registry's table with `dyn_inventory::export!`, and
+            ///   not export another function under its symbol
+            /// - the library must be built against a version of the crate declaring this registry
+            ///   which declares the registry and its trait as the host does. its name, module and
+            ///   the size of a registration are checked, but changes which keep those are not
+            ///   detected. with `api_version`, plugins built against an incompatible api version are
+            ///   reported in `incompatible` and never instantiated, so their trait may differ
+            /// - the library and the host must use the same global allocator, as the host drops the
+            ///   handles and values the library allocated. neither may declare a
+            ///   `#[global_allocator]` the other does not
+            /// - the file must not be modified in place while it is loaded
+            pub unsafe fn load(
+                &mut self,
+                path: impl ::core::convert::AsRef<::dyn_inventory_runtime::std::ffi::OsStr>,
+            ) -> ::core::result::Result<(), #err> {
+                // SAFETY: upheld by the caller
+                unsafe { self.load_with(path, |_| {}) }
+            }
+
+            /// # Safety
+            ///
+            /// see [`Self::load`].
+            pub unsafe fn load_with<F: ::core::ops::Fn(&mut #strct)>(
+                &mut self,
+                path: impl ::core::convert::AsRef<::dyn_inventory_runtime::std::ffi::OsStr>,
+                with: F,
+            ) -> ::core::result::Result<(), #err> {
+                // SAFETY: upheld by the caller
+                let library = unsafe { ::dyn_inventory_runtime::Library::open(path) }?;
+                // SAFETY: as above
+                unsafe { self.load_library_with(library, with) }
+            }
+
+            /// loads the plugins exported by a library which is already open, adding them to this
+            /// collection.
+            ///
+            /// # Safety
+            ///
+            /// see [`Self::load`].
+            pub unsafe fn load_library_with<F: ::core::ops::Fn(&mut #strct)>(
+                &mut self,
+                library: ::dyn_inventory_runtime::Library,
+                with: F,
+            ) -> ::core::result::Result<(), #err> {
+                // SAFETY: `export!` exports the table under this symbol, and the registry identifies
+                // the registration type. registrations built against an incompatible api version
+                // are set aside by `build`, without being instantiated
+                let inits = unsafe {
+                    library.entries::<#struct_init>(
+                        #symbol,
+                        #struct_init::__REGISTRY,
+                        #struct_init::API_VERSION,
+                    )
+                }?
+                .to_vec();
+
+                let loaded = Self::build(inits, with)#built;
+                self.plugins.extend(loaded.plugins);
+                #(#merge)*
+                #reindex
+                self.libraries.push(library);
+                ::core::result::Result::Ok(())
+            }
+        }
+
+        /// reloads the plugins of the shared libraries in a directory, alongside the plugins which
+        /// are linked in.
+        ///
+        /// each reload builds a new collection, from fresh copies of the libraries. plugins of previous
+        /// collections remain valid while they are held. every copy whose registrations were read
+        /// stays loaded for the rest of the process, so each reload keeps the previous copies in
+        /// memory. the reloader counts every copy it loads, and refuses to load more than its
+        /// limit, [`Self::DEFAULT_LIMIT`] unless set with [`Self::with_limit`].
+        pub struct #reloader {
+            dir: ::dyn_inventory_runtime::std::path::PathBuf,
+            limit: usize,
+            loaded: ::core::sync::atomic::AtomicUsize,
+            current: ::dyn_inventory_runtime::RwLock<::dyn_inventory_runtime::std::sync::Arc<#collector>>,
+            generation: ::core::sync::atomic::AtomicU64,
+            files: ::dyn_inventory_runtime::std::sync::Mutex<
+                ::dyn_inventory_runtime::alloc::vec::Vec<::dyn_inventory_runtime::LibraryFile>,
+            >,
+        }
+
+        impl #reloader {
+            /// how many copies of libraries a reloader loads over its lifetime, unless set with
+            /// [`Self::with_limit`]
+            pub const DEFAULT_LIMIT: usize = 256;
+
+            /// collects the plugins which are linked in, and loads every shared library in `dir`.
+            ///
+            /// # Safety
+            ///
+            #[doc = #safety]
+            /// this applies to every library which is in `dir` whenever the plugins are reloaded.
+            pub unsafe fn new(
+                dir: impl ::core::convert::Into<::dyn_inventory_runtime::std::path::PathBuf>,
+            ) -> ::core::result::Result<Self, #err> {
+                // SAFETY: upheld by the caller
+                unsafe { Self::with_limit(dir, Self::DEFAULT_LIMIT) }
+            }
+
+            /// like [`Self::new`], but loads at most `limit` copies of libraries over the lifetime of
+            /// the reloader, counting the libraries loaded now. a reload which would load more fails
+            /// with [`LoadError::Limit`], and keeps the current collection.
+            ///
+            /// [`LoadError::Limit`]: ::dyn_inventory_runtime::LoadError::Limit
+            ///
+            /// # Safety
+            ///
+            #[doc = #safety]
+            /// this applies to every library which is in `dir` whenever the plugins are reloaded.
+            pub unsafe fn with_limit(
+                dir: impl ::core::convert::Into<::dyn_inventory_runtime::std::path::PathBuf>,
+                limit: usize,
+            ) -> ::core::result::Result<Self, #err> {
+                let dir = dir.into();
+                let files = ::dyn_inventory_runtime::scan(&dir)?;
+                if files.len() > limit {
+                    return ::core::result::Result::Err(
+                        ::dyn_inventory_runtime::LoadError::Limit { dir, limit }.into(),
+                    );
+                }
+                // SAFETY: upheld by the caller
+                let current = unsafe { Self::collect(&files) }?;
+                ::core::result::Result::Ok(Self {
+                    limit,
+                    loaded: ::core::sync::atomic::AtomicUsize::new(files.len()),
+                    dir,
+                    current: ::dyn_inventory_runtime::RwLock::new(::dyn_inventory_runtime::std::sync::Arc::new(current)),
+                    generation: ::core::sync::atomic::AtomicU64::new(0),
+                    files: ::dyn_inventory_runtime::std::sync::Mutex::new(files),
+                })
+            }
+
+            /// # Safety
+            ///
+            #[doc = #safety]
+            unsafe fn collect(files: &[::dyn_inventory_runtime::LibraryFile]) -> ::core::result::Result<#collector, #err> {
+                let mut collected = #collector::build(#collector::registered(), |_| {})#built;
+                for file in files {
+                    // a copy is opened, as the previous version of the library may still be loaded
+                    // SAFETY: upheld by the caller
+                    let library = unsafe { ::dyn_inventory_runtime::Library::open_copy(&file.path) }?;
+                    // SAFETY: as above
+                    unsafe { collected.load_library_with(library, |_| {}) }?;
+                }
+                ::core::result::Result::Ok(collected)
+            }
+
+            /// the directory the libraries are loaded from
+            pub fn dir(&self) -> &::dyn_inventory_runtime::std::path::Path {
+                &self.dir
+            }
+
+            /// the current collection. it remains valid while it is held, even once the plugins are
+            /// reloaded.
+            pub fn current(&self) -> ::dyn_inventory_runtime::std::sync::Arc<#collector> {
+                self.current.read().clone()
+            }
+
+            /// how many copies of libraries were loaded, each of which stays loaded for the rest of
+            /// the process
+            pub fn loaded(&self) -> usize {
+                self.loaded.load(::core::sync::atomic::Ordering::Acquire)
+            }
+
+            /// how many copies of libraries the reloader loads at most
+            pub fn limit(&self) -> usize {
+                self.limit
+            }
+
+            /// how many times the plugins were reloaded
+            pub fn generation(&self) -> u64 {
+                self.generation.load(::core::sync::atomic::Ordering::Acquire)
+            }
+
+            /// whether the libraries in the directory changed since they were last loaded
+            pub fn changed(&self) -> ::core::result::Result<bool, #err> {
+                let files = self
+                    .files
+                    .lock()
+                    .unwrap_or_else(::dyn_inventory_runtime::std::sync::PoisonError::into_inner);
+                ::core::result::Result::Ok(::dyn_inventory_runtime::scan(&self.dir)? != *files)
+            }
+
+            /// reloads the plugins, returning the new generation.
+            ///
+            /// when any library fails to load, such as a library built for a different abi, or loading
+            /// the libraries would exceed the limit of the reloader, the current collection is kept
+            /// and the error returned.
+            ///
+            /// # Safety
+            ///
+            #[doc = #safety]
+            pub unsafe fn reload(&self) -> ::core::result::Result<u64, #err> {
+                let mut files = self
+                    .files
+                    .lock()
+                    .unwrap_or_else(::dyn_inventory_runtime::std::sync::PoisonError::into_inner);
+                let scanned = ::dyn_inventory_runtime::scan(&self.dir)?;
+                // SAFETY: upheld by the caller
+                unsafe { self.swap(&mut files, scanned) }
+            }
+
+            /// reloads the plugins if the libraries in the directory changed, returning the new
+            /// generation.
+            ///
+            /// # Safety
+            ///
+            #[doc = #safety]
+            pub unsafe fn reload_if_changed(
+                &self,
+            ) -> ::core::result::Result<::core::option::Option<u64>, #err> {
+                let mut files = self
+                    .files
+                    .lock()
+                    .unwrap_or_else(::dyn_inventory_runtime::std::sync::PoisonError::into_inner);
+                let scanned = ::dyn_inventory_runtime::scan(&self.dir)?;
+                if scanned == *files {
+                    return ::core::result::Result::Ok(::core::option::Option::None);
+                }
+                // SAFETY: upheld by the caller
+                unsafe { self.swap(&mut files, scanned) }.map(::core::option::Option::Some)
+            }
+
+            /// # Safety
+            ///
+            #[doc = #safety]
+            unsafe fn swap(
+                &self,
+                files: &mut ::dyn_inventory_runtime::alloc::vec::Vec<::dyn_inventory_runtime::LibraryFile>,
+                scanned: ::dyn_inventory_runtime::alloc::vec::Vec<::dyn_inventory_runtime::LibraryFile>,
+            ) -> ::core::result::Result<u64, #err> {
+                // copies are counted once they are attempted, as those loaded before a failing one
+                // stay loaded too
+                let loaded = self.loaded() + scanned.len();
+                if loaded > self.limit {
+                    return ::core::result::Result::Err(
+                        ::dyn_inventory_runtime::LoadError::Limit {
+                            dir: self.dir.clone(),
+                            limit: self.limit,
+                        }
+                        .into(),
+                    );
+                }
+                self.loaded.store(loaded, ::core::sync::atomic::Ordering::Release);
+                // SAFETY: upheld by the caller
+                let collected = unsafe { Self::collect(&scanned) }?;
+                *self.current.write() = ::dyn_inventory_runtime::std::sync::Arc::new(collected);
+                *files = scanned;
+                ::core::result::Result::Ok(self.generation.fetch_add(1, ::core::sync::atomic::Ordering::AcqRel) + 1)
+            }
+        }
+    }
+}
+
+/// the arguments to `export!`, the name of the registry.
+pub struct ExportArgs {
+    /// the registry struct whose plugins are exported
+    pub plugin_name: Ident,
+}
+
+impl Parse for ExportArgs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            plugin_name: input.parse()?,
+        })
+    }
+}
+
+impl ToTokens for ExportArgs {
+    fn to_tokens(
+        &self,
+        tokens: &mut TokenStream,
+    ) {
+        let struct_init = crate::init(&self.plugin_name);
+        let symbol = symbol(&self.plugin_name);
+
+        tokens.extend(quote::quote! {
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            #[unsafe(no_mangle)]
+            pub extern "C" fn #symbol() -> ::dyn_inventory_runtime::Table {
+                #struct_init::__export()
+            }
+        });
+    }
+}
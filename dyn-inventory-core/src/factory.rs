@@ -0,0 +1,354 @@
+use proc_macro2::TokenStream;
+
+use syn::Ident;
+
+use crate::args::{Args, Field};
+
+/// the fields of a plugin: the metadata as given by `metadata`, and the handle from `output`, the
+/// output of a factory.
+fn fields(
+    args: &Args,
+    output: TokenStream,
+    metadata: impl Fn(&Ident) -> TokenStream,
+) -> TokenStream {
+    args.collect_fields_or_generic(
+        |Field { name, .. }| {
+            let metadata = metadata(name);
+            quote::quote! {
+                #name: #metadata,
+            }
+        },
+        |Field { name, .. }| {
+            let get = if args.async_factory {
+                quote::quote! { #output.await }
+            } else {
+                quote::quote! { #output }
+            };
+            if args.error.is_some() {
+                quote::quote! {
+                    #name: #get?,
+                }
+            } else {
+                quote::quote! {
+                    #name: #get,
+                }
+            }
+        },
+        args.generic_param.to_string(),
+    )
+}
+
+/// generates the instantiation of registrations into plugins, and the adapters `emit!` uses to
+/// turn a handle or a factory into the registration's factory.
+///
+/// each plugin keeps the factory it was instantiated by, so further instances can be created from
+/// it.
+///
+/// `emit!` cannot see the declaration of the registry, so it only ever refers to
+/// `StructNameInit::__handle` and `StructNameInit::__factory`, which are generated here to match
+/// the registry's options.
+pub fn expand(args: &Args) -> TokenStream {
+    let trt = &args.trait_name;
+    let strct = &args.struct_name;
+    let struct_init = &args.init_name;
+    let output = args.factory_output();
+
+    let fields_as_into = fields(args, quote::quote! { value.__get.call() }, |name| {
+        quote::quote! { value.#name }
+    });
+    // a configured plugin deserializes the section it was configured with again, which succeeded
+    // when it was collected
+    let get = match (args.config, &args.key) {
+        (true, Some(key)) => {
+            quote::quote! {
+                self.__get.call()
+                    .configure(self.#key, &self.__section)
+                    .expect("the section of the plugin was valid when it was collected")()
+            }
+        },
+        _ => quote::quote! { self.__get.call() },
+    };
+    let fields_cloned = fields(args, get, |name| {
+        quote::quote! { ::core::clone::Clone::clone(&self.#name) }
+    });
+    let section_cloned = args.config.then(|| {
+        quote::quote! { __section: ::core::clone::Clone::clone(&self.__section), }
+    });
+    let instance = quote::quote! {
+        Self {
+            #fields_cloned
+            __get: ::core::clone::Clone::clone(&self.__get),
+            __source: self.__source,
+            #section_cloned
+        }
+    };
+    let new_instance = match (args.async_factory, &args.error) {
+        (true, None) => {
+            quote::quote! {
+                pub async fn new_instance(&self) -> Self {
+                    #instance
+                }
+            }
+        },
+        (true, Some(err)) => {
+            quote::quote! {
+                pub async fn new_instance(&self) -> ::core::result::Result<Self, #err> {
+                    ::core::result::Result::Ok(#instance)
+                }
+            }
+        },
+        (false, None) => {
+            quote::quote! {
+                pub fn new_instance(&self) -> Self {
+                    #instance
+                }
+            }
+        },
+        (false, Some(err)) => {
+            quote::quote! {
+                pub fn new_instance(&self) -> ::core::result::Result<Self, #err> {
+                    ::core::result::Result::Ok(#instance)
+                }
+            }
+        },
+    };
+
+    let inner = args.handle_output();
+    // the generics of the handle or factory passed to each adapter, what a factory returns, and how
+    // the handle or that output becomes the handle output
+    let (handle_generics, handle, factory_generics, factory_ty, convert) =
+        match (args.async_factory, &args.error) {
+            (true, None) => {
+                (
+                    quote::quote! { H: #trt + ::core::marker::Send + 'static },
+                    quote::quote! {
+                        ::dyn_inventory_runtime::alloc::boxed::Box::pin(async move {
+                            ::dyn_inventory_runtime::alloc::boxed::Box::new(handle)
+                                as ::dyn_inventory_runtime::alloc::boxed::Box<dyn #trt>
+                        })
+                    },
+                    quote::quote! {
+                        H: #trt + 'static,
+                        Fut: ::core::future::Future<Output = H> + ::core::marker::Send + 'static,
+                    },
+                    quote::quote! { Fut },
+                    quote::quote! {
+                        ::dyn_inventory_runtime::alloc::boxed::Box::pin(async move {
+                            ::dyn_inventory_runtime::alloc::boxed::Box::new(value.await)
+                                as ::dyn_inventory_runtime::alloc::boxed::Box<dyn #trt>
+                        })
+                    },
+                )
+            },
+            (true, Some(err)) => {
+                (
+                    quote::quote! { H: #trt + ::core::marker::Send + 'static },
+                    quote::quote! {
+                        ::dyn_inventory_runtime::alloc::boxed::Box::pin(async move {
+                            ::core::result::Result::Ok(
+                                ::dyn_inventory_runtime::alloc::boxed::Box::new(handle)
+                                    as ::dyn_inventory_runtime::alloc::boxed::Box<dyn #trt>,
+                            )
+                        })
+                    },
+                    quote::quote! {
+                        H: #trt + 'static,
+                        Fut: ::core::future::Future<Output = ::core::result::Result<H, #err>>
+                            + ::core::marker::Send
+                            + 'static,
+                    },
+                    quote::quote! { Fut },
+                    quote::quote! {
+                        ::dyn_inventory_runtime::alloc::boxed::Box::pin(async move {
+                            value.await.map(|handle| {
+                                ::dyn_inventory_runtime::alloc::boxed::Box::new(handle)
+                                    as ::dyn_inventory_runtime::alloc::boxed::Box<dyn #trt>
+                            })
+                        })
+                    },
+                )
+            },
+            (false, None) => {
+                (
+                    quote::quote! { H: #trt + 'static },
+                    quote::quote! {
+                        ::dyn_inventory_runtime::alloc::boxed::Box::new(handle)
+                            as ::dyn_inventory_runtime::alloc::boxed::Box<dyn #trt>
+                    },
+                    quote::quote! { H: #trt + 'static, },
+                    quote::quote! { H },
+                    quote::quote! {
+                        ::dyn_inventory_runtime::alloc::boxed::Box::new(value)
+                            as ::dyn_inventory_runtime::alloc::boxed::Box<dyn #trt>
+                    },
+                )
+            },
+            (false, Some(err)) => {
+                (
+                    quote::quote! { H: #trt + 'static },
+                    quote::quote! {
+                        ::core::result::Result::Ok(
+                            ::dyn_inventory_runtime::alloc::boxed::Box::new(handle)
+                                as ::dyn_inventory_runtime::alloc::boxed::Box<dyn #trt>,
+                        )
+                    },
+                    quote::quote! { H: #trt + 'static, },
+                    quote::quote! { ::core::result::Result<H, #err> },
+                    quote::quote! {
+                        value.map(|handle| {
+                            ::dyn_inventory_runtime::alloc::boxed::Box::new(handle)
+                                as ::dyn_inventory_runtime::alloc::boxed::Box<dyn #trt>
+                        })
+                    },
+                )
+            },
+        };
+
+    // configured registries defer the factory until the section of the plugin is deserialized into
+    // the configuration it takes
+    let adapters = if args.config {
+        quote::quote! {
+            #[doc(hidden)]
+            pub fn __handle<#handle_generics>(handle: H) -> #output {
+                ::dyn_inventory_runtime::config::Configurable::new(move || -> #inner { #handle })
+            }
+
+            #[doc(hidden)]
+            pub fn __factory<
+                Args,
+                #factory_generics
+                F: ::dyn_inventory_runtime::config::Factory<Args, #factory_ty>,
+            >(
+                factory: F,
+            ) -> #output {
+                ::dyn_inventory_runtime::config::Factory::into_configurable(factory)
+                    .map(|value| -> #inner { #convert })
+            }
+        }
+    } else {
+        quote::quote! {
+            #[doc(hidden)]
+            pub fn __handle<#handle_generics>(handle: H) -> #output {
+                #handle
+            }
+
+            #[doc(hidden)]
+            pub fn __factory<#factory_generics F: ::core::ops::FnOnce() -> #factory_ty>(factory: F) -> #output {
+                let value = factory();
+                #convert
+            }
+        }
+    };
+
+    let instance_ty = match &args.error {
+        Some(err) => quote::quote! { ::core::result::Result<#strct, #err> },
+        None => quote::quote! { #strct },
+    };
+    let built = quote::quote! {
+        #strct {
+            #fields_as_into
+            __get: value.__get,
+            __source: value.__source,
+        }
+    };
+    let built = if args.error.is_some() {
+        quote::quote! { ::core::result::Result::Ok(#built) }
+    } else {
+        built
+    };
+
+    let instantiate = match (args.config, args.async_factory, &args.error) {
+        (true, ..) => {
+            let key = args
+                .key
+                .as_ref()
+                .expect("`config` requires a key, which is checked while parsing");
+            let fields_configured = fields(args, quote::quote! { factory() }, |name| {
+                quote::quote! { value.#name }
+            });
+            let configured = quote::quote! {
+                #strct {
+                    #fields_configured
+                    __get: value.__get,
+                    __source: value.__source,
+                    __section: section,
+                        }
+            };
+            let configured = if args.error.is_some() {
+                quote::quote! { ::core::result::Result::Ok(#configured) }
+            } else {
+                configured
+            };
+            let (deferred_ty, deferred) = if args.async_factory {
+                (
+                    quote::quote! { impl ::core::future::Future<Output = #instance_ty> },
+                    quote::quote! { async move { #configured } },
+                )
+            } else {
+                (
+                    quote::quote! { impl ::core::ops::FnOnce() -> #instance_ty },
+                    quote::quote! { move || #configured },
+                )
+            };
+            quote::quote! {
+                impl #struct_init {
+                    /// deserializes the configuration of this registration from `section`. the
+                    /// factory runs with it once the returned instantiation is called.
+                    pub fn configure(
+                        self,
+                        section: ::dyn_inventory_runtime::config::Section,
+                    ) -> ::core::result::Result<#deferred_ty, ::dyn_inventory_runtime::config::ConfigError> {
+                        let value = self;
+                        let factory = value.__get.call().configure(value.#key, &section)?;
+                        ::core::result::Result::Ok(#deferred)
+                    }
+                }
+            }
+        },
+        (false, true, _) => {
+            quote::quote! {
+                impl #struct_init {
+                    /// runs the factory of this registration
+                    pub async fn instantiate(self) -> #instance_ty {
+                        let value = self;
+                        #built
+                    }
+                }
+            }
+        },
+        (false, false, Some(_)) => {
+            quote::quote! {
+                impl #struct_init {
+                    /// runs the factory of this registration
+                    pub fn instantiate(self) -> #instance_ty {
+                        let value = self;
+                        #built
+                    }
+                }
+            }
+        },
+        (false, false, None) => {
+            quote::quote! {
+                impl ::core::convert::From<#struct_init> for #strct {
+                    fn from(value: #struct_init) -> Self {
+                        #built
+                    }
+                }
+            }
+        },
+    };
+
+    quote::quote! {
+        impl #struct_init {
+            #adapters
+        }
+
+        #instantiate
+
+        impl #strct {
+            /// runs the factory of this plugin again, for an instance independent of this one. the
+            /// metadata is cloned, and `with` callbacks of the collection are not applied.
+            #new_instance
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! the parser and code generator behind [`dyn-inventory`](https://docs.rs/dyn-inventory).
+//!
+//! `dyn-inventory` is a thin proc-macro shim over this crate. each of its macros parses its input
+//! into one of the models below and expands it with [`quote::ToTokens`], so other macros can
+//! declare registries, or build on a parsed declaration, without going through `dyn_inventory!`:
+//!
+//! ```rust
+//! use dyn_inventory_core::Args;
+//!
+//! let args: Args = syn::parse_quote! {
+//!     Plugin<Handle: Service> {
+//!         name: &'static str,
+//!         handle: Handle
+//!     };
+//!     key = name,
+//! };
+//! assert_eq!(args.handle_field().name, "handle");
+//! assert_eq!(args.key.as_ref().unwrap(), "name");
+//!
+//! let expanded = quote::quote! { #args };
+//! assert!(
+//!     expanded
+//!         .to_string()
+//!         .contains("PluginCollector")
+//! );
+//! ```
+//!
+//! the functions named after each macro expand a token stream, and report parse errors as a
+//! `compile_error!` within the expansion.
+
+#![warn(missing_docs)]
+
+macro_rules! kws {
+    (
+        $input: ident & $kw: ident
+    ) => {
+
+        if $input.peek(kw::$kw) {
+            let _: kw::$kw = $input.parse()?;
+            let _: syn::Token![=] = $input.parse()?;
+            return Ok(Self::$kw {
+                value: $input.parse()?,
+            });
+        }
+    };
+    ( $input: ident & [$(
+        $kw: ident
+    ), + $(,)?]) => {
+        $(
+            kws!{ $input & $kw }
+        )*
+        Err(
+            syn::Error::new(
+                $input.span(),
+                "unknown keyword",
+            )
+        )
+    };
+}
+
+mod args;
+mod asyncness;
+mod backend;
+mod clap;
+mod collector;
+mod config;
+mod declare;
+mod deps;
+mod downcast;
+mod dylib;
+mod factory;
+mod lifecycle;
+mod metadata;
+mod registry;
+mod select;
+mod tags;
+mod version;
+
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::{Ident, parse::Parse};
+
+pub use crate::{
+    args::{Args, Field, RefOrTy},
+    asyncness::DynAsyncArgs,
+    backend::Backend,
+    declare::{EmitArgs, FieldValue, MockArgs},
+    dylib::ExportArgs,
+};
+
+pub(crate) fn init(strct: &Ident) -> Ident {
+    Ident::new(&format!("{strct}Init"), Span::call_site())
+}
+
+fn expand<T: Parse + ToTokens>(tok: TokenStream) -> TokenStream {
+    match syn::parse2::<T>(tok) {
+        Ok(parsed) => parsed.into_token_stream(),
+        Err(e) => e.into_compile_error(),
+    }
+}
+
+/// expands `dyn_inventory!`, the declaration of a registry
+pub fn dyn_inventory(tok: TokenStream) -> TokenStream {
+    expand::<Args>(tok)
+}
+
+/// expands `emit!`, the submission of a plugin
+pub fn emit(tok: TokenStream) -> TokenStream {
+    expand::<EmitArgs>(tok)
+}
+
+/// expands `mock_plugin!`, a registration which is not submitted
+pub fn mock_plugin(tok: TokenStream) -> TokenStream {
+    expand::<MockArgs>(tok)
+}
+
+/// expands `export!`, the entry point of a registry built as a dynamic library
+pub fn export(tok: TokenStream) -> TokenStream {
+    expand::<ExportArgs>(tok)
+}
+
+/// expands `#[dyn_async]` on a trait or impl, with the arguments of the attribute in `attr`
+pub fn dyn_async(
+    attr: TokenStream,
+    item: TokenStream,
+) -> TokenStream {
+    let args: DynAsyncArgs = match syn::parse2(attr) {
+        Ok(args) => args,
+        Err(e) => return e.into_compile_error(),
+    };
+    let item = match syn::parse2(item) {
+        Ok(item) => item,
+        Err(e) => return e.into_compile_error(),
+    };
+
+    match asyncness::expand(args, item) {
+        Ok(expanded) => expanded,
+        Err(e) => e.into_compile_error(),
+    }
+}
@@ -0,0 +1,101 @@
+use proc_macro2::TokenStream;
+
+use crate::args::Args;
+
+/// generates the managed collector, which starts and stops each plugin with its lifecycle hooks.
+///
+/// the trait must declare `dyn_inventory_runtime::Lifecycle` as a supertrait. plugins start in the
+/// order they were collected, and stop in reverse, whether the managed collector is shut down or
+/// dropped. when a plugin fails to start, the plugins which started are stopped before the failure
+/// is returned.
+///
+/// the managed collector only hands out started plugins, so plugins can't be used once they have
+/// been stopped.
+pub fn expand(args: &Args) -> TokenStream {
+    let strct = &args.struct_name;
+    let collector = args.derived_name("Collector");
+    let managed = args.derived_name("Managed");
+    let handle = &args.handle_field().name;
+    let key = match &args.key {
+        Some(key) => quote::quote! { ::core::option::Option::Some(plugin.#key) },
+        None => quote::quote! { ::core::option::Option::None },
+    };
+    let get = args.key.as_ref().map(|key| {
+        quote::quote! {
+            /// the started plugin registered under `key`
+            pub fn get(&self, key: &str) -> ::core::option::Option<&#strct> {
+                self.plugins().iter().find(|plugin| plugin.#key == key)
+            }
+        }
+    });
+
+    quote::quote! {
+        /// the plugins of a collector, started with [`Lifecycle::on_load`](::dyn_inventory_runtime::Lifecycle::on_load)
+        /// and stopped in reverse order with [`Lifecycle::on_unload`](::dyn_inventory_runtime::Lifecycle::on_unload)
+        /// when shut down or dropped.
+        pub struct #managed {
+            collector: #collector,
+            /// how many plugins, from the front of `collector.plugins`, have started
+            started: usize,
+        }
+
+        impl #collector {
+            /// starts every plugin, in the order they were collected. when a plugin fails to start,
+            /// the plugins which started are stopped in reverse order, and the failure is returned.
+            pub fn start(self) -> ::core::result::Result<#managed, ::dyn_inventory_runtime::StartError> {
+                let mut managed = #managed {
+                    collector: self,
+                    started: 0,
+                };
+                while managed.started < managed.collector.plugins.len() {
+                    let plugin: &mut #strct = &mut managed.collector.plugins[managed.started];
+                    if let ::core::result::Result::Err(error) =
+                        ::dyn_inventory_runtime::Lifecycle::on_load(&mut *plugin.#handle)
+                    {
+                        let err = ::dyn_inventory_runtime::StartError {
+                            key: #key,
+                            source: plugin.__source,
+                            error,
+                            stopped: managed.started,
+                        };
+                        managed.shutdown();
+                        return ::core::result::Result::Err(err);
+                    }
+                    managed.started += 1;
+                }
+                ::core::result::Result::Ok(managed)
+            }
+        }
+
+        impl #managed {
+            /// the started plugins, none once shut down
+            pub fn plugins(&self) -> &[#strct] {
+                &self.collector.plugins[..self.started]
+            }
+
+            #get
+
+            /// whether any plugin is still started, i.e. it was not shut down
+            pub fn is_running(&self) -> bool {
+                self.started > 0
+            }
+
+            /// stops every started plugin, in the reverse of the order they started. called when
+            /// dropped, and does nothing once every plugin has stopped.
+            pub fn shutdown(&mut self) {
+                while self.started > 0 {
+                    self.started -= 1;
+                    ::dyn_inventory_runtime::Lifecycle::on_unload(
+                        &mut *self.collector.plugins[self.started].#handle,
+                    );
+                }
+            }
+        }
+
+        impl ::core::ops::Drop for #managed {
+            fn drop(&mut self) {
+                self.shutdown();
+            }
+        }
+    }
+}
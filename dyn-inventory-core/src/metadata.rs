@@ -0,0 +1,116 @@
+use proc_macro2::TokenStream;
+
+use crate::args::{Args, Field};
+
+/// generates a serializable view of the metadata fields of each plugin, skipping the handle.
+///
+/// `#[serde(..)]` attributes declared on the fields are applied to the view, so fields can be
+/// renamed or skipped. without `serde = true`, `#[serde(..)]` attributes are rejected instead.
+pub fn expand(args: &Args) -> TokenStream {
+    if !args.serde {
+        return args
+            .fields
+            .iter()
+            .flat_map(|field| &field.serde_attrs)
+            .map(|attr| {
+                syn::Error::new_spanned(attr, "`#[serde(..)]` attributes require `serde = true`")
+                    .into_compile_error()
+            })
+            .collect();
+    }
+
+    let strct = &args.struct_name;
+    let struct_init = &args.init_name;
+    let collector = args.derived_name("Collector");
+    let view = args.derived_name("Metadata");
+    let generic = args.generic_param.to_string();
+
+    let handle_attrs = args.collect_fields_or_generic(
+        |_| quote::quote! {},
+        |Field { serde_attrs, .. }| {
+            serde_attrs
+                .iter()
+                .map(|attr| {
+                    syn::Error::new_spanned(attr, "the plugin handle is never serialized")
+                        .into_compile_error()
+                })
+                .collect()
+        },
+        generic.clone(),
+    );
+    if !handle_attrs.is_empty() {
+        return handle_attrs;
+    }
+
+    let view_fields = args.collect_fields_or_generic(
+        |Field {
+             serde_attrs,
+             vis,
+             name,
+             ty,
+             ..
+         }| {
+            quote::quote! {
+                #(#serde_attrs)*
+                #vis #name: &'a #ty,
+            }
+        },
+        |_| quote::quote! {},
+        generic.clone(),
+    );
+
+    let view_init = args.collect_fields_or_generic(
+        |Field { name, .. }| {
+            quote::quote! {
+                #name: &self.#name,
+            }
+        },
+        |_| quote::quote! {},
+        generic,
+    );
+
+    quote::quote! {
+        #[derive(::serde::Serialize)]
+        pub struct #view<'a> {
+            #view_fields
+            #[serde(skip)]
+            __marker: ::core::marker::PhantomData<&'a ()>,
+        }
+
+        impl #strct {
+            pub fn metadata(&self) -> #view<'_> {
+                #view {
+                    #view_init
+                    __marker: ::core::marker::PhantomData,
+                }
+            }
+        }
+
+        impl #struct_init {
+            pub fn metadata(&self) -> #view<'_> {
+                #view {
+                    #view_init
+                    __marker: ::core::marker::PhantomData,
+                }
+            }
+        }
+
+        impl ::serde::Serialize for #strct {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error> {
+                ::serde::Serialize::serialize(&self.metadata(), serializer)
+            }
+        }
+
+        impl ::serde::Serialize for #struct_init {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error> {
+                ::serde::Serialize::serialize(&self.metadata(), serializer)
+            }
+        }
+
+        impl ::serde::Serialize for #collector {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error> {
+                serializer.collect_seq(&self.plugins)
+            }
+        }
+    }
+}
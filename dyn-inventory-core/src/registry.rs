@@ -0,0 +1,97 @@
+use proc_macro2::TokenStream;
+use syn::Ident;
+
+use crate::args::Args;
+
+/// generates a registry which holds the link-time registrations alongside those registered at
+/// runtime, keyed by `key`.
+///
+/// the registrations are held behind a lock, so that plugins can be registered through a shared
+/// reference. collectors are built from a snapshot of the registrations.
+pub fn expand(
+    args: &Args,
+    key: &Ident,
+) -> TokenStream {
+    let strct = &args.struct_name;
+    let struct_init = &args.init_name;
+    let collector = args.derived_name("Collector");
+    let registry = args.derived_name("Registry");
+
+    let build_ty = match &args.depends_on {
+        Some(_) => {
+            let err = args.derived_name("DependencyError");
+            quote::quote! { ::core::result::Result<#collector, #err> }
+        },
+        None => quote::quote! { #collector },
+    };
+
+    quote::quote! {
+        /// the registrations of every plugin submitted at link time, and those registered at runtime.
+        pub struct #registry {
+            inits: ::dyn_inventory_runtime::RwLock<::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>>,
+        }
+
+        impl ::core::default::Default for #registry {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl #registry {
+            /// starts from the registrations submitted at link time
+            pub fn new() -> Self {
+                Self::from_inits(#collector::registered())
+            }
+
+            /// starts from `inits` instead of the registrations submitted at link time
+            pub fn from_inits(inits: impl ::core::iter::IntoIterator<Item = #struct_init>) -> Self {
+                Self {
+                    inits: ::dyn_inventory_runtime::RwLock::new(inits.into_iter().collect()),
+                }
+            }
+
+            /// registers a plugin, replacing the registration with the same key, which is returned
+            pub fn register(&self, init: #struct_init) -> ::core::option::Option<#struct_init> {
+                let mut inits = self.inits.write();
+                match inits.iter_mut().find(|registered| registered.#key == init.#key) {
+                    ::core::option::Option::Some(registered) => ::core::option::Option::Some(::core::mem::replace(registered, init)),
+                    ::core::option::Option::None => {
+                        inits.push(init);
+                        ::core::option::Option::None
+                    },
+                }
+            }
+
+            /// removes the registration with `key`, whether it was submitted at link time or registered at runtime
+            pub fn unregister(&self, key: &str) -> ::core::option::Option<#struct_init> {
+                let mut inits = self.inits.write();
+                let idx = inits.iter().position(|registered| registered.#key == key)?;
+                ::core::option::Option::Some(inits.remove(idx))
+            }
+
+            /// whether a plugin is registered under `key`
+            pub fn contains(&self, key: &str) -> bool {
+                self.inits.read().iter().any(|registered| registered.#key == key)
+            }
+
+            /// the keys of every registered plugin, in registration order
+            pub fn keys(&self) -> ::dyn_inventory_runtime::alloc::vec::Vec<&'static str> {
+                self.inits.read().iter().map(|registered| registered.#key).collect()
+            }
+
+            /// a snapshot of the current registrations
+            pub fn inits(&self) -> ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init> {
+                self.inits.read().clone()
+            }
+
+            /// collects the currently registered plugins
+            pub fn collect(&self) -> #build_ty {
+                self.collect_with(|_| {})
+            }
+
+            pub fn collect_with<F: ::core::ops::Fn(&mut #strct)>(&self, with: F) -> #build_ty {
+                #collector::from_inits_with(self.inits(), with)
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use proc_macro2::TokenStream;
+use syn::Ident;
+
+use crate::args::Args;
+
+/// generates allow / deny list selection of plugins by key, and the optional environment
+/// variable convention built on top of it.
+pub fn expand(
+    args: &Args,
+    key: &Ident,
+) -> TokenStream {
+    let strct = &args.struct_name;
+    let struct_init = &args.init_name;
+    let collector = args.derived_name("Collector");
+    let err = args.derived_name("SelectionError");
+    let registry_name = strct.to_string();
+
+    let (dependency_variant, dependency_display, dependency_from, build) = match &args.depends_on {
+        Some(_) => {
+            let dependency_err = args.derived_name("DependencyError");
+            (
+                quote::quote! {
+                    /// the selected plugins could not be ordered by their dependencies
+                    Dependency(#dependency_err),
+                },
+                quote::quote! {
+                    Self::Dependency(err) => ::core::write!(f, "{err}"),
+                },
+                quote::quote! {
+                    impl ::core::convert::From<#dependency_err> for #err {
+                        fn from(err: #dependency_err) -> Self {
+                            Self::Dependency(err)
+                        }
+                    }
+                },
+                quote::quote! {
+                    ::core::result::Result::Ok(Self::build(inits, with)?)
+                },
+            )
+        },
+        None => {
+            (
+                quote::quote! {},
+                quote::quote! {},
+                quote::quote! {},
+                quote::quote! {
+                    ::core::result::Result::Ok(Self::build(inits, with))
+                },
+            )
+        },
+    };
+
+    let (env_variant, env_display, from_env) = match &args.env_prefix {
+        Some(prefix) => {
+            let enable = format!("{}_ENABLE", prefix.value());
+            let disable = format!("{}_DISABLE", prefix.value());
+            let from_env_doc = format!(
+                "collects the plugins selected by the comma-separated `{enable}` and `{disable}` environment variables. an unset `{enable}` enables every plugin."
+            );
+            (
+                quote::quote! {
+                    /// the environment variable is set, but is not valid unicode
+                    Env { var: &'static str },
+                },
+                quote::quote! {
+                    Self::Env { var } => ::core::write!(f, "`{var}` is not valid unicode"),
+                },
+                quote::quote! {
+                    impl #collector {
+                        #[doc = #from_env_doc]
+                        pub fn from_env() -> ::core::result::Result<Self, #err> {
+                            Self::from_env_with(|_| {})
+                        }
+
+                        pub fn from_env_with<F: ::core::ops::Fn(&mut #strct)>(with: F) -> ::core::result::Result<Self, #err> {
+                            fn names(
+                                var: &'static str,
+                            ) -> ::core::result::Result<::dyn_inventory_runtime::alloc::vec::Vec<::dyn_inventory_runtime::alloc::string::String>, #err> {
+                                match ::dyn_inventory_runtime::std::env::var(var) {
+                                    ::core::result::Result::Ok(value) => {
+                                        ::core::result::Result::Ok(value
+                                            .split(',')
+                                            .map(::core::primitive::str::trim)
+                                            .filter(|name| !name.is_empty())
+                                            .map(::dyn_inventory_runtime::alloc::string::String::from)
+                                            .collect())
+                                    },
+                                    ::core::result::Result::Err(::dyn_inventory_runtime::std::env::VarError::NotPresent) => ::core::result::Result::Ok(::dyn_inventory_runtime::alloc::vec::Vec::new()),
+                                    ::core::result::Result::Err(::dyn_inventory_runtime::std::env::VarError::NotUnicode(_)) => {
+                                        ::core::result::Result::Err(#err::Env { var })
+                                    },
+                                }
+                            }
+
+                            let allow = names(#enable)?;
+                            let deny = names(#disable)?;
+                            let allow: ::dyn_inventory_runtime::alloc::vec::Vec<&str> = allow.iter().map(::dyn_inventory_runtime::alloc::string::String::as_str).collect();
+                            let deny: ::dyn_inventory_runtime::alloc::vec::Vec<&str> = deny.iter().map(::dyn_inventory_runtime::alloc::string::String::as_str).collect();
+                            Self::from_selection_with(&allow, &deny, with)
+                        }
+                    }
+                },
+            )
+        },
+        None => (quote::quote! {}, quote::quote! {}, quote::quote! {}),
+    };
+
+    quote::quote! {
+        #[derive(::core::fmt::Debug, ::core::clone::Clone, ::core::cmp::PartialEq, ::core::cmp::Eq)]
+        pub enum #err {
+            /// `name` was listed in an allow or deny list, but no plugin is registered under it
+            Unknown { name: ::dyn_inventory_runtime::alloc::string::String },
+            #dependency_variant
+            #env_variant
+        }
+
+        impl ::core::fmt::Display for #err {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    Self::Unknown { name } => ::core::write!(f, "no plugin is registered as `{name}`"),
+                    #dependency_display
+                    #env_display
+                }
+            }
+        }
+
+        impl ::core::error::Error for #err {}
+
+        #dependency_from
+
+        impl #collector {
+            fn select(
+                inits: ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>,
+                allow: &[&str],
+                deny: &[&str],
+            ) -> ::core::result::Result<::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>, #err> {
+                for name in allow.iter().chain(deny) {
+                    if !inits.iter().any(|init| init.#key == *name) {
+                        return ::core::result::Result::Err(#err::Unknown {
+                            name: ::dyn_inventory_runtime::alloc::string::String::from(*name),
+                        });
+                    }
+                }
+
+                let registered = inits.len();
+                let selected: ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init> = inits
+                    .into_iter()
+                    .filter(|init| allow.is_empty() || allow.contains(&init.#key))
+                    .filter(|init| !deny.contains(&init.#key))
+                    .collect();
+                ::dyn_inventory_runtime::trace::select(#registry_name, registered, selected.len());
+                ::core::result::Result::Ok(selected)
+            }
+
+            /// collects the plugins whose key is in `allow` (or every plugin when `allow` is empty), except those in `deny`.
+            pub fn from_selection(allow: &[&str], deny: &[&str]) -> ::core::result::Result<Self, #err> {
+                Self::from_selection_with(allow, deny, |_| {})
+            }
+
+            pub fn from_selection_with<F: ::core::ops::Fn(&mut #strct)>(
+                allow: &[&str],
+                deny: &[&str],
+                with: F,
+            ) -> ::core::result::Result<Self, #err> {
+                let inits = Self::select(Self::registered(), allow, deny)?;
+                #build
+            }
+        }
+
+        #from_env
+    }
+}
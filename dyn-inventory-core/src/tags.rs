@@ -0,0 +1,123 @@
+use proc_macro2::TokenStream;
+use syn::Ident;
+
+use crate::args::Args;
+
+/// generates the tag index of the collector, and lookups of plugins by tag.
+///
+/// each plugin is indexed under each of its tags, and each parent category of a hierarchical tag:
+/// a plugin tagged `io/compression` is found by both `io/compression` and `io`. the index maps each
+/// tag to the positions of its plugins in registration order, so lookups never scan every plugin.
+/// `plugins` is private with tags, and is only changed through methods which rebuild the index, so
+/// the positions never go stale.
+pub fn expand(
+    args: &Args,
+    tags: &Ident,
+) -> TokenStream {
+    let strct = &args.struct_name;
+    let collector = args.derived_name("Collector");
+
+    quote::quote! {
+        impl #strct {
+            /// the tags of this plugin, as registered
+            pub fn tags(&self) -> &'static [&'static str] {
+                self.#tags
+            }
+
+            /// whether this plugin is tagged with `tag`, or a tag within the category `tag`
+            pub fn has_tag(&self, tag: &str) -> bool {
+                self.#tags.iter().any(|registered| {
+                    registered
+                        .strip_prefix(tag)
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+                })
+            }
+        }
+
+        impl #collector {
+            fn index_tags(&mut self) {
+                self.tag_index.clear();
+                for (idx, plugin) in self.plugins.iter().enumerate() {
+                    for tag in plugin.#tags {
+                        let tag: &'static str = tag;
+                        let categories = tag
+                            .match_indices('/')
+                            .map(|(end, _)| &tag[..end])
+                            .chain([tag]);
+                        for category in categories {
+                            let indexed = self.tag_index.entry(category).or_default();
+                            if indexed.last() != ::core::option::Option::Some(&idx) {
+                                indexed.push(idx);
+                            }
+                        }
+                    }
+                }
+            }
+
+            fn tagged(&self, tag: &str) -> &[usize] {
+                self.tag_index
+                    .get(tag)
+                    .map(::dyn_inventory_runtime::alloc::vec::Vec::as_slice)
+                    .unwrap_or_default()
+            }
+
+            /// the collected plugins, in registration order
+            pub fn plugins(&self) -> &[#strct] {
+                &self.plugins
+            }
+
+            /// takes the collected plugins out of the collector
+            pub fn into_plugins(self) -> ::dyn_inventory_runtime::alloc::vec::Vec<#strct> {
+                self.plugins
+            }
+
+            /// keeps only the plugins for which `keep` returns true, and reindexes the rest
+            pub fn retain(&mut self, keep: impl ::core::ops::FnMut(&#strct) -> bool) {
+                self.plugins.retain(keep);
+                self.index_tags();
+            }
+
+            /// adds `plugins` after the collected plugins, such as those of another collector, and
+            /// indexes them
+            pub fn extend(&mut self, plugins: impl ::core::iter::IntoIterator<Item = #strct>) {
+                self.plugins.extend(plugins);
+                self.index_tags();
+            }
+
+            /// every tag and category any plugin is indexed under, sorted
+            pub fn tags(&self) -> ::dyn_inventory_runtime::alloc::vec::Vec<&'static str> {
+                self.tag_index.keys().copied().collect()
+            }
+
+            /// the plugins tagged with `tag`, or a tag within the category `tag`, in registration order
+            pub fn by_tag(&self, tag: &str) -> impl ::core::iter::Iterator<Item = &#strct> {
+                self.tagged(tag).iter().map(|idx| &self.plugins[*idx])
+            }
+
+            /// the plugins tagged with every one of `tags`, in registration order. like
+            /// [`Iterator::all`], no tags at all match every plugin.
+            pub fn by_tags_all(&self, tags: &[&str]) -> ::dyn_inventory_runtime::alloc::vec::Vec<&#strct> {
+                let mut indexed: ::dyn_inventory_runtime::alloc::vec::Vec<&[usize]> =
+                    tags.iter().map(|tag| self.tagged(tag)).collect();
+                indexed.sort_by_key(|indexed| indexed.len());
+                let ::core::option::Option::Some((smallest, rest)) = indexed.split_first() else {
+                    return self.plugins.iter().collect();
+                };
+                smallest
+                    .iter()
+                    .filter(|idx| rest.iter().all(|indexed| indexed.binary_search(idx).is_ok()))
+                    .map(|idx| &self.plugins[*idx])
+                    .collect()
+            }
+
+            /// the plugins tagged with any of `tags`, in registration order
+            pub fn by_tags_any(&self, tags: &[&str]) -> ::dyn_inventory_runtime::alloc::vec::Vec<&#strct> {
+                let mut matched: ::dyn_inventory_runtime::alloc::vec::Vec<usize> =
+                    tags.iter().flat_map(|tag| self.tagged(tag)).copied().collect();
+                matched.sort_unstable();
+                matched.dedup();
+                matched.into_iter().map(|idx| &self.plugins[idx]).collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use proc_macro2::TokenStream;
+
+use crate::args::Args;
+
+/// generates the api version check, which sets aside registrations built against an api version
+/// that is not compatible with the one declared by the registry.
+pub fn expand(args: &Args) -> TokenStream {
+    let struct_init = &args.init_name;
+    let collector = args.derived_name("Collector");
+    let version = &args.api_version;
+
+    quote::quote! {
+        impl #collector {
+            fn check_api_versions(
+                inits: ::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>,
+            ) -> (::dyn_inventory_runtime::alloc::vec::Vec<#struct_init>, ::dyn_inventory_runtime::alloc::vec::Vec<(#struct_init, ::dyn_inventory_runtime::ApiMismatch)>) {
+                let mut compatible = ::dyn_inventory_runtime::alloc::vec::Vec::new();
+                let mut incompatible = ::dyn_inventory_runtime::alloc::vec::Vec::new();
+                for init in inits {
+                    match ::dyn_inventory_runtime::check_api_version(#version, init.__api_version) {
+                        ::core::result::Result::Ok(()) => compatible.push(init),
+                        ::core::result::Result::Err(err) => incompatible.push((init, err)),
+                    }
+                }
+                (compatible, incompatible)
+            }
+        }
+    }
+}
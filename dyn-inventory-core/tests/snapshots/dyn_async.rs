@@ -0,0 +1,16 @@
+pub trait Pool {
+    fn size<'dyn_async>(
+        &'dyn_async self,
+    ) -> ::dyn_inventory_runtime::BoxFuture<'dyn_async, usize>;
+    fn resize<'dyn_async>(
+        &'dyn_async mut self,
+        by: &'dyn_async usize,
+    ) -> ::dyn_inventory_runtime::BoxFuture<'dyn_async, usize>
+    where
+        Self: ::core::marker::Send,
+    {
+        ::dyn_inventory_runtime::alloc::boxed::Box::pin(async move {
+            self.size().await + by
+        })
+    }
+}
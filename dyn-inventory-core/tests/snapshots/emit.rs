@@ -0,0 +1,10 @@
+pub struct Handle;
+const _: () = ::core::assert!(
+    PluginInit::__LINKME == false,
+    "`PluginInit` is collected with linkme, but this plugin was submitted with inventory. pass `backend = linkme` to `emit!`",
+);
+::inventory::submit! {
+    PluginInit { name : "http", __get : ::dyn_inventory_runtime::Constructor::new(|| {
+    PluginInit::__handle:: < Handle > (Handle) }), __source :
+    ::dyn_inventory_runtime::source!(), __api_version : PluginInit::API_VERSION, }
+}
@@ -0,0 +1,16 @@
+struct Handle;
+const _: () = {
+    const _: () = ::core::assert!(
+        PluginInit::__LINKME == true,
+        "`PluginInit` is collected with inventory, but this plugin was submitted with linkme. pass `backend = inventory` to `emit!`",
+    );
+    #[::linkme::distributed_slice(PluginInit)]
+    static REGISTRATION: PluginInit = PluginInit {
+        name: "http",
+        __get: ::dyn_inventory_runtime::Constructor::new(|| {
+            PluginInit::__handle::<Handle>(Handle)
+        }),
+        __source: ::dyn_inventory_runtime::source!(),
+        __api_version: PluginInit::API_VERSION,
+    };
+};
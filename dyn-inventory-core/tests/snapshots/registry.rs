@@ -0,0 +1,156 @@
+#[derive(::core::clone::Clone)]
+pub struct PluginInit {
+    pub name: &'static str,
+    __get: ::dyn_inventory_runtime::Constructor<
+        ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service>,
+    >,
+    #[doc(hidden)]
+    pub __source: ::dyn_inventory_runtime::Source,
+    #[doc(hidden)]
+    pub __api_version: ::core::option::Option<&'static str>,
+}
+impl PluginInit {
+    /// the api version declared by the registry, which `emit!` stamps into each registration
+    pub const API_VERSION: ::core::option::Option<&'static str> = ::core::option::Option::None;
+    #[doc(hidden)]
+    pub const __LINKME: bool = false;
+    /// every registration submitted at link time
+    pub fn iter() -> impl ::core::iter::Iterator<Item = &'static Self> {
+        ::inventory::iter::<PluginInit>.into_iter()
+    }
+    /// the api version this plugin was built against
+    pub fn api_version(&self) -> ::core::option::Option<&'static str> {
+        self.__api_version
+    }
+    /// where this plugin was registered
+    pub fn source(&self) -> &::dyn_inventory_runtime::Source {
+        &self.__source
+    }
+}
+pub struct Plugin {
+    pub name: &'static str,
+    handle: ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service>,
+    __get: ::dyn_inventory_runtime::Constructor<
+        ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service>,
+    >,
+    __source: ::dyn_inventory_runtime::Source,
+}
+impl Plugin {
+    /// where this plugin was registered
+    pub fn source(&self) -> &::dyn_inventory_runtime::Source {
+        &self.__source
+    }
+}
+impl PluginInit {
+    #[doc(hidden)]
+    pub fn __handle<H: Service + 'static>(
+        handle: H,
+    ) -> ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service> {
+        ::dyn_inventory_runtime::alloc::boxed::Box::new(handle)
+            as ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service>
+    }
+    #[doc(hidden)]
+    pub fn __factory<H: Service + 'static, F: ::core::ops::FnOnce() -> H>(
+        factory: F,
+    ) -> ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service> {
+        let value = factory();
+        ::dyn_inventory_runtime::alloc::boxed::Box::new(value)
+            as ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service>
+    }
+}
+impl ::core::convert::From<PluginInit> for Plugin {
+    fn from(value: PluginInit) -> Self {
+        Plugin {
+            name: value.name,
+            handle: value.__get.call(),
+            __get: value.__get,
+            __source: value.__source,
+        }
+    }
+}
+impl Plugin {
+    /// runs the factory of this plugin again, for an instance independent of this one. the
+    /// metadata is cloned, and `with` callbacks of the collection are not applied.
+    pub fn new_instance(&self) -> Self {
+        Self {
+            name: ::core::clone::Clone::clone(&self.name),
+            handle: self.__get.call(),
+            __get: ::core::clone::Clone::clone(&self.__get),
+            __source: self.__source,
+        }
+    }
+}
+::inventory::collect! {
+    PluginInit
+}
+pub struct PluginCollector {
+    pub plugins: ::dyn_inventory_runtime::alloc::vec::Vec<Plugin>,
+}
+impl PluginCollector {
+    pub fn new() -> Self {
+        Self::new_with(|_| {})
+    }
+    pub fn new_with<F: ::core::ops::Fn(&mut Plugin)>(with: F) -> Self {
+        Self::build(Self::registered(), with)
+    }
+    /// collects the plugins of `inits` instead of the registered plugins, such as registrations
+    /// built with `mock_plugin!`.
+    pub fn from_inits(
+        inits: impl ::core::iter::IntoIterator<Item = PluginInit>,
+    ) -> Self {
+        Self::from_inits_with(inits, |_| {})
+    }
+    pub fn from_inits_with<F: ::core::ops::Fn(&mut Plugin)>(
+        inits: impl ::core::iter::IntoIterator<Item = PluginInit>,
+        with: F,
+    ) -> Self {
+        Self::build(inits.into_iter().collect(), with)
+    }
+    fn registered() -> ::dyn_inventory_runtime::alloc::vec::Vec<PluginInit> {
+        PluginInit::iter().cloned().collect()
+    }
+    fn build<F: ::core::ops::Fn(&mut Plugin)>(
+        inits: ::dyn_inventory_runtime::alloc::vec::Vec<PluginInit>,
+        with: F,
+    ) -> Self {
+        let collection = ::dyn_inventory_runtime::trace::Collection::start(
+            "Plugin",
+            inits.len(),
+        );
+        let mut plugins = ::dyn_inventory_runtime::alloc::vec::Vec::new();
+        for plugin in inits {
+            let construction = collection
+                .construct(::core::option::Option::None, &plugin.__source);
+            let mut plugin = construction
+                .in_scope(|| <Plugin as ::core::convert::From<
+                    PluginInit,
+                >>::from(plugin));
+            construction.finish(::dyn_inventory_runtime::trace::Outcome::Loaded);
+            with(&mut plugin);
+            plugins.push(plugin);
+        }
+        collection.finish(plugins.len());
+        Self { plugins }
+    }
+}
+impl ::dyn_inventory_runtime::Entry for Plugin {
+    type Handle = dyn Service;
+    fn handle(&self) -> &Self::Handle {
+        &*self.handle
+    }
+    fn source(&self) -> &::dyn_inventory_runtime::Source {
+        &self.__source
+    }
+}
+impl ::dyn_inventory_runtime::Registry for PluginCollector {
+    type Entry = Plugin;
+    type Init = PluginInit;
+    type Handle = dyn Service;
+    type Error = ::core::convert::Infallible;
+    fn try_collect() -> ::core::result::Result<
+        ::dyn_inventory_runtime::alloc::vec::Vec<Self::Entry>,
+        Self::Error,
+    > {
+        ::core::result::Result::Ok(Self::new().plugins)
+    }
+}
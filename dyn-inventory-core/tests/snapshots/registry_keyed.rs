@@ -0,0 +1,474 @@
+#[derive(::core::clone::Clone)]
+pub struct PluginInit {
+    pub name: &'static str,
+    pub depends_on: &'static [&'static str],
+    __get: ::dyn_inventory_runtime::Constructor<
+        ::core::result::Result<
+            ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service>,
+            Unavailable,
+        >,
+    >,
+    #[doc(hidden)]
+    pub __source: ::dyn_inventory_runtime::Source,
+    #[doc(hidden)]
+    pub __api_version: ::core::option::Option<&'static str>,
+}
+impl PluginInit {
+    /// the api version declared by the registry, which `emit!` stamps into each registration
+    pub const API_VERSION: ::core::option::Option<&'static str> = ::core::option::Option::None;
+    #[doc(hidden)]
+    pub const __LINKME: bool = false;
+    /// every registration submitted at link time
+    pub fn iter() -> impl ::core::iter::Iterator<Item = &'static Self> {
+        ::inventory::iter::<PluginInit>.into_iter()
+    }
+    /// the api version this plugin was built against
+    pub fn api_version(&self) -> ::core::option::Option<&'static str> {
+        self.__api_version
+    }
+    /// where this plugin was registered
+    pub fn source(&self) -> &::dyn_inventory_runtime::Source {
+        &self.__source
+    }
+}
+pub struct Plugin {
+    pub name: &'static str,
+    pub depends_on: &'static [&'static str],
+    handle: ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service>,
+    __get: ::dyn_inventory_runtime::Constructor<
+        ::core::result::Result<
+            ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service>,
+            Unavailable,
+        >,
+    >,
+    __source: ::dyn_inventory_runtime::Source,
+}
+impl Plugin {
+    /// where this plugin was registered
+    pub fn source(&self) -> &::dyn_inventory_runtime::Source {
+        &self.__source
+    }
+}
+impl PluginInit {
+    #[doc(hidden)]
+    pub fn __handle<H: Service + 'static>(
+        handle: H,
+    ) -> ::core::result::Result<
+        ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service>,
+        Unavailable,
+    > {
+        ::core::result::Result::Ok(
+            ::dyn_inventory_runtime::alloc::boxed::Box::new(handle)
+                as ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service>,
+        )
+    }
+    #[doc(hidden)]
+    pub fn __factory<
+        H: Service + 'static,
+        F: ::core::ops::FnOnce() -> ::core::result::Result<H, Unavailable>,
+    >(
+        factory: F,
+    ) -> ::core::result::Result<
+        ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service>,
+        Unavailable,
+    > {
+        let value = factory();
+        value
+            .map(|handle| {
+                ::dyn_inventory_runtime::alloc::boxed::Box::new(handle)
+                    as ::dyn_inventory_runtime::alloc::boxed::Box<dyn Service>
+            })
+    }
+}
+impl PluginInit {
+    /// runs the factory of this registration
+    pub fn instantiate(self) -> ::core::result::Result<Plugin, Unavailable> {
+        let value = self;
+        ::core::result::Result::Ok(Plugin {
+            name: value.name,
+            depends_on: value.depends_on,
+            handle: value.__get.call()?,
+            __get: value.__get,
+            __source: value.__source,
+        })
+    }
+}
+impl Plugin {
+    /// runs the factory of this plugin again, for an instance independent of this one. the
+    /// metadata is cloned, and `with` callbacks of the collection are not applied.
+    pub fn new_instance(&self) -> ::core::result::Result<Self, Unavailable> {
+        ::core::result::Result::Ok(Self {
+            name: ::core::clone::Clone::clone(&self.name),
+            depends_on: ::core::clone::Clone::clone(&self.depends_on),
+            handle: self.__get.call()?,
+            __get: ::core::clone::Clone::clone(&self.__get),
+            __source: self.__source,
+        })
+    }
+}
+::inventory::collect! {
+    PluginInit
+}
+pub struct PluginCollector {
+    pub plugins: ::dyn_inventory_runtime::alloc::vec::Vec<Plugin>,
+    /// registrations whose factory returned an error, with that error
+    pub failed: ::dyn_inventory_runtime::alloc::vec::Vec<(PluginInit, Unavailable)>,
+}
+impl PluginCollector {
+    pub fn new() -> Self {
+        Self::new_with(|_| {})
+    }
+    pub fn new_with<F: ::core::ops::Fn(&mut Plugin)>(with: F) -> Self {
+        match Self::build(Self::registered(), with) {
+            ::core::result::Result::Ok(this) => this,
+            ::core::result::Result::Err(err) => ::core::panic!("{err}"),
+        }
+    }
+    /// collects the plugins of `inits` instead of the registered plugins, such as registrations
+    /// built with `mock_plugin!`.
+    pub fn from_inits(
+        inits: impl ::core::iter::IntoIterator<Item = PluginInit>,
+    ) -> ::core::result::Result<Self, PluginDependencyError> {
+        Self::from_inits_with(inits, |_| {})
+    }
+    pub fn from_inits_with<F: ::core::ops::Fn(&mut Plugin)>(
+        inits: impl ::core::iter::IntoIterator<Item = PluginInit>,
+        with: F,
+    ) -> ::core::result::Result<Self, PluginDependencyError> {
+        Self::build(inits.into_iter().collect(), with)
+    }
+    fn registered() -> ::dyn_inventory_runtime::alloc::vec::Vec<PluginInit> {
+        PluginInit::iter().cloned().collect()
+    }
+    fn build<F: ::core::ops::Fn(&mut Plugin)>(
+        inits: ::dyn_inventory_runtime::alloc::vec::Vec<PluginInit>,
+        with: F,
+    ) -> ::core::result::Result<Self, PluginDependencyError> {
+        let collection = ::dyn_inventory_runtime::trace::Collection::start(
+            "Plugin",
+            inits.len(),
+        );
+        let inits = Self::order(inits)?;
+        let mut failed = ::dyn_inventory_runtime::alloc::vec::Vec::new();
+        let mut plugins = ::dyn_inventory_runtime::alloc::vec::Vec::new();
+        for plugin in inits {
+            let construction = collection
+                .construct(::core::option::Option::Some(plugin.name), &plugin.__source);
+            let mut plugin = match construction.in_scope(|| plugin.clone().instantiate())
+            {
+                ::core::result::Result::Ok(instance) => instance,
+                ::core::result::Result::Err(err) => {
+                    construction.finish(::dyn_inventory_runtime::trace::Outcome::Failed);
+                    failed.push((plugin, err));
+                    continue;
+                }
+            };
+            construction.finish(::dyn_inventory_runtime::trace::Outcome::Loaded);
+            with(&mut plugin);
+            plugins.push(plugin);
+        }
+        collection.finish(plugins.len());
+        ::core::result::Result::Ok(Self { plugins, failed })
+    }
+}
+impl Plugin {
+    pub fn key(&self) -> &'static str {
+        self.name
+    }
+}
+impl PluginCollector {
+    pub fn get(&self, key: &str) -> ::core::option::Option<&Plugin> {
+        self.plugins.iter().find(|plugin| plugin.name == key)
+    }
+    /// a new instance of the plugin registered under `key`, independent of the collected one
+    pub fn spawn(
+        &self,
+        key: &str,
+    ) -> ::core::option::Option<::core::result::Result<Plugin, Unavailable>> {
+        self.get(key).map(Plugin::new_instance)
+    }
+}
+#[derive(
+    ::core::fmt::Debug,
+    ::core::clone::Clone,
+    ::core::cmp::PartialEq,
+    ::core::cmp::Eq
+)]
+pub enum PluginDependencyError {
+    /// more than one plugin was registered under the same key
+    Duplicate {
+        plugin: &'static str,
+        first: ::dyn_inventory_runtime::Source,
+        second: ::dyn_inventory_runtime::Source,
+    },
+    /// `plugin` depends on `dependency`, which is not registered
+    Missing { plugin: &'static str, dependency: &'static str },
+    /// the plugins form a dependency cycle, in order. the last plugin depends on the first.
+    Cycle { plugins: ::dyn_inventory_runtime::alloc::vec::Vec<&'static str> },
+}
+impl ::core::fmt::Display for PluginDependencyError {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        match self {
+            Self::Duplicate { plugin, first, second } => {
+                ::core::write!(
+                    f,
+                    "plugin `{plugin}` is registered more than once, by {first} and {second}",
+                )
+            }
+            Self::Missing { plugin, dependency } => {
+                ::core::write!(
+                    f,
+                    "plugin `{plugin}` depends on `{dependency}`, which is not registered",
+                )
+            }
+            Self::Cycle { plugins } => {
+                ::core::write!(f, "dependency cycle: ")?;
+                for plugin in plugins {
+                    ::core::write!(f, "`{plugin}` -> ")?;
+                }
+                match plugins.first() {
+                    ::core::option::Option::Some(first) => ::core::write!(f, "`{first}`"),
+                    ::core::option::Option::None => ::core::result::Result::Ok(()),
+                }
+            }
+        }
+    }
+}
+impl ::core::error::Error for PluginDependencyError {}
+impl PluginCollector {
+    fn order(
+        inits: ::dyn_inventory_runtime::alloc::vec::Vec<PluginInit>,
+    ) -> ::core::result::Result<
+        ::dyn_inventory_runtime::alloc::vec::Vec<PluginInit>,
+        PluginDependencyError,
+    > {
+        fn visit(
+            idx: usize,
+            inits: &[PluginInit],
+            index: &::dyn_inventory_runtime::alloc::collections::BTreeMap<
+                &'static str,
+                usize,
+            >,
+            state: &mut [u8],
+            path: &mut ::dyn_inventory_runtime::alloc::vec::Vec<usize>,
+            order: &mut ::dyn_inventory_runtime::alloc::vec::Vec<usize>,
+        ) -> ::core::result::Result<(), PluginDependencyError> {
+            match state[idx] {
+                2 => return ::core::result::Result::Ok(()),
+                1 => {
+                    let start = path.iter().position(|p| *p == idx).unwrap_or_default();
+                    return ::core::result::Result::Err(PluginDependencyError::Cycle {
+                        plugins: path[start..].iter().map(|p| inits[*p].name).collect(),
+                    });
+                }
+                _ => {}
+            }
+            state[idx] = 1;
+            path.push(idx);
+            for dependency in inits[idx].depends_on {
+                match index.get(dependency) {
+                    ::core::option::Option::Some(dep) => {
+                        visit(*dep, inits, index, state, path, order)?
+                    }
+                    ::core::option::Option::None => {
+                        return ::core::result::Result::Err(PluginDependencyError::Missing {
+                            plugin: inits[idx].name,
+                            dependency,
+                        });
+                    }
+                }
+            }
+            path.pop();
+            state[idx] = 2;
+            order.push(idx);
+            ::core::result::Result::Ok(())
+        }
+        let mut index = ::dyn_inventory_runtime::alloc::collections::BTreeMap::new();
+        for (idx, init) in inits.iter().enumerate() {
+            if let ::core::option::Option::Some(first) = index.insert(init.name, idx) {
+                return ::core::result::Result::Err(PluginDependencyError::Duplicate {
+                    plugin: init.name,
+                    first: inits[first].__source,
+                    second: init.__source,
+                });
+            }
+        }
+        let mut state = ::dyn_inventory_runtime::alloc::vec![0u8; inits.len()];
+        let mut path = ::dyn_inventory_runtime::alloc::vec::Vec::new();
+        let mut order = ::dyn_inventory_runtime::alloc::vec::Vec::with_capacity(
+            inits.len(),
+        );
+        for idx in 0..inits.len() {
+            visit(idx, &inits, &index, &mut state, &mut path, &mut order)?;
+        }
+        ::core::result::Result::Ok(
+            order.into_iter().map(|idx| inits[idx].clone()).collect(),
+        )
+    }
+    pub fn try_new() -> ::core::result::Result<Self, PluginDependencyError> {
+        Self::try_new_with(|_| {})
+    }
+    pub fn try_new_with<F: ::core::ops::Fn(&mut Plugin)>(
+        with: F,
+    ) -> ::core::result::Result<Self, PluginDependencyError> {
+        Self::build(Self::registered(), with)
+    }
+}
+#[derive(
+    ::core::fmt::Debug,
+    ::core::clone::Clone,
+    ::core::cmp::PartialEq,
+    ::core::cmp::Eq
+)]
+pub enum PluginSelectionError {
+    /// `name` was listed in an allow or deny list, but no plugin is registered under it
+    Unknown { name: ::dyn_inventory_runtime::alloc::string::String },
+    /// the selected plugins could not be ordered by their dependencies
+    Dependency(PluginDependencyError),
+}
+impl ::core::fmt::Display for PluginSelectionError {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        match self {
+            Self::Unknown { name } => {
+                ::core::write!(f, "no plugin is registered as `{name}`")
+            }
+            Self::Dependency(err) => ::core::write!(f, "{err}"),
+        }
+    }
+}
+impl ::core::error::Error for PluginSelectionError {}
+impl ::core::convert::From<PluginDependencyError> for PluginSelectionError {
+    fn from(err: PluginDependencyError) -> Self {
+        Self::Dependency(err)
+    }
+}
+impl PluginCollector {
+    fn select(
+        inits: ::dyn_inventory_runtime::alloc::vec::Vec<PluginInit>,
+        allow: &[&str],
+        deny: &[&str],
+    ) -> ::core::result::Result<
+        ::dyn_inventory_runtime::alloc::vec::Vec<PluginInit>,
+        PluginSelectionError,
+    > {
+        for name in allow.iter().chain(deny) {
+            if !inits.iter().any(|init| init.name == *name) {
+                return ::core::result::Result::Err(PluginSelectionError::Unknown {
+                    name: ::dyn_inventory_runtime::alloc::string::String::from(*name),
+                });
+            }
+        }
+        let registered = inits.len();
+        let selected: ::dyn_inventory_runtime::alloc::vec::Vec<PluginInit> = inits
+            .into_iter()
+            .filter(|init| allow.is_empty() || allow.contains(&init.name))
+            .filter(|init| !deny.contains(&init.name))
+            .collect();
+        ::dyn_inventory_runtime::trace::select("Plugin", registered, selected.len());
+        ::core::result::Result::Ok(selected)
+    }
+    /// collects the plugins whose key is in `allow` (or every plugin when `allow` is empty), except those in `deny`.
+    pub fn from_selection(
+        allow: &[&str],
+        deny: &[&str],
+    ) -> ::core::result::Result<Self, PluginSelectionError> {
+        Self::from_selection_with(allow, deny, |_| {})
+    }
+    pub fn from_selection_with<F: ::core::ops::Fn(&mut Plugin)>(
+        allow: &[&str],
+        deny: &[&str],
+        with: F,
+    ) -> ::core::result::Result<Self, PluginSelectionError> {
+        let inits = Self::select(Self::registered(), allow, deny)?;
+        ::core::result::Result::Ok(Self::build(inits, with)?)
+    }
+}
+/// the registrations of every plugin submitted at link time, and those registered at runtime.
+pub struct PluginRegistry {
+    inits: ::dyn_inventory_runtime::RwLock<
+        ::dyn_inventory_runtime::alloc::vec::Vec<PluginInit>,
+    >,
+}
+impl ::core::default::Default for PluginRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl PluginRegistry {
+    /// starts from the registrations submitted at link time
+    pub fn new() -> Self {
+        Self::from_inits(PluginCollector::registered())
+    }
+    /// starts from `inits` instead of the registrations submitted at link time
+    pub fn from_inits(
+        inits: impl ::core::iter::IntoIterator<Item = PluginInit>,
+    ) -> Self {
+        Self {
+            inits: ::dyn_inventory_runtime::RwLock::new(inits.into_iter().collect()),
+        }
+    }
+    /// registers a plugin, replacing the registration with the same key, which is returned
+    pub fn register(&self, init: PluginInit) -> ::core::option::Option<PluginInit> {
+        let mut inits = self.inits.write();
+        match inits.iter_mut().find(|registered| registered.name == init.name) {
+            ::core::option::Option::Some(registered) => {
+                ::core::option::Option::Some(::core::mem::replace(registered, init))
+            }
+            ::core::option::Option::None => {
+                inits.push(init);
+                ::core::option::Option::None
+            }
+        }
+    }
+    /// removes the registration with `key`, whether it was submitted at link time or registered at runtime
+    pub fn unregister(&self, key: &str) -> ::core::option::Option<PluginInit> {
+        let mut inits = self.inits.write();
+        let idx = inits.iter().position(|registered| registered.name == key)?;
+        ::core::option::Option::Some(inits.remove(idx))
+    }
+    /// whether a plugin is registered under `key`
+    pub fn contains(&self, key: &str) -> bool {
+        self.inits.read().iter().any(|registered| registered.name == key)
+    }
+    /// the keys of every registered plugin, in registration order
+    pub fn keys(&self) -> ::dyn_inventory_runtime::alloc::vec::Vec<&'static str> {
+        self.inits.read().iter().map(|registered| registered.name).collect()
+    }
+    /// a snapshot of the current registrations
+    pub fn inits(&self) -> ::dyn_inventory_runtime::alloc::vec::Vec<PluginInit> {
+        self.inits.read().clone()
+    }
+    /// collects the currently registered plugins
+    pub fn collect(
+        &self,
+    ) -> ::core::result::Result<PluginCollector, PluginDependencyError> {
+        self.collect_with(|_| {})
+    }
+    pub fn collect_with<F: ::core::ops::Fn(&mut Plugin)>(
+        &self,
+        with: F,
+    ) -> ::core::result::Result<PluginCollector, PluginDependencyError> {
+        PluginCollector::from_inits_with(self.inits(), with)
+    }
+}
+impl ::dyn_inventory_runtime::Entry for Plugin {
+    type Handle = dyn Service;
+    fn handle(&self) -> &Self::Handle {
+        &*self.handle
+    }
+    fn source(&self) -> &::dyn_inventory_runtime::Source {
+        &self.__source
+    }
+}
+impl ::dyn_inventory_runtime::Registry for PluginCollector {
+    type Entry = Plugin;
+    type Init = PluginInit;
+    type Handle = dyn Service;
+    type Error = PluginDependencyError;
+    fn try_collect() -> ::core::result::Result<
+        ::dyn_inventory_runtime::alloc::vec::Vec<Self::Entry>,
+        Self::Error,
+    > {
+        ::core::result::Result::map(Self::try_new(), |collected| collected.plugins)
+    }
+}
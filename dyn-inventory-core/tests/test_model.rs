@@ -0,0 +1,86 @@
+use dyn_inventory_core::{Args, Backend, EmitArgs, RefOrTy};
+
+#[test]
+fn args() {
+    let args: Args = syn::parse_quote! {
+        Plugin<Handle: Service> {
+            /// the name of the plugin
+            pub name: &'static str,
+            pub tags: &'static [&'static str],
+            handle: Handle
+        };
+        key = name,
+        tags = tags,
+        init_name = Registration,
+        async_factory = true,
+        backend = linkme,
+    };
+
+    assert_eq!(args.struct_name, "Plugin");
+    assert_eq!(args.trait_name, "Service");
+    assert_eq!(args.generic_param, "Handle");
+    assert_eq!(args.init_name, "Registration");
+    assert_eq!(args.key.as_ref().unwrap(), "name");
+    assert_eq!(args.tags.as_ref().unwrap(), "tags");
+    assert!(args.async_factory);
+    assert!(!args.config);
+    assert_eq!(args.backend, Backend::Linkme);
+    assert_eq!(args.derived_name("Collector"), "PluginCollector");
+
+    let fields: Vec<_> = args
+        .fields
+        .iter()
+        .map(|field| field.name.to_string())
+        .collect();
+    assert_eq!(fields, ["name", "tags", "handle"]);
+    assert_eq!(args.fields[0].attrs.len(), 1);
+    assert!(matches!(args.fields[0].ty, RefOrTy::Ref(_)));
+    assert!(matches!(args.handle_field().ty, RefOrTy::Ty(_)));
+}
+
+#[test]
+fn args_defaults() {
+    let args: Args = syn::parse_quote! {
+        Plugin<Handle: Service> {
+            handle: Handle
+        }
+    };
+
+    assert_eq!(args.init_name, "PluginInit");
+    assert_eq!(args.key, None);
+    assert_eq!(args.backend, Backend::default());
+}
+
+#[test]
+fn args_invalid() {
+    let err = syn::parse2::<Args>(quote::quote! {
+        Plugin<Handle: Service> {
+            handle: Handle
+        };
+        key = handle,
+    })
+    .err()
+    .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "`handle` is the plugin handle and cannot be used as metadata"
+    );
+}
+
+#[test]
+fn emit_args() {
+    let emit: EmitArgs = syn::parse_quote! {
+        Handle Service as Plugin {
+            name = "http",
+        };
+        factory = || Handle,
+        backend = inventory,
+    };
+
+    assert_eq!(emit.handle_name, "Handle");
+    assert_eq!(emit.trait_name, "Service");
+    assert_eq!(emit.plugin_name, "Plugin");
+    assert_eq!(emit.fields[0].name, "name");
+    assert!(emit.factory.is_some());
+    assert_eq!(emit.backend, Backend::Inventory);
+}
@@ -0,0 +1,150 @@
+use proc_macro2::TokenStream;
+
+/// pretty-prints `expanded` and compares it with `tests/snapshots/{name}.rs`.
+///
+/// run with `SNAPSHOTS=overwrite` to accept a changed expansion.
+fn snapshot(
+    name: &str,
+    expanded: TokenStream,
+) {
+    let file = syn::parse2(expanded).expect("the expansion is a list of items");
+    let expanded = prettyplease::unparse(&file);
+    let path = format!("{}/tests/snapshots/{name}.rs", env!("CARGO_MANIFEST_DIR"));
+
+    if std::env::var_os("SNAPSHOTS").is_some_and(|mode| mode == "overwrite") {
+        std::fs::write(&path, expanded).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("{path} is missing ({err}), run with SNAPSHOTS=overwrite"));
+    assert!(
+        expected == expanded,
+        "the expansion of {name} changed, run with SNAPSHOTS=overwrite to accept it"
+    );
+}
+
+#[test_case::test_case("registry", quote::quote! {
+    Plugin<Handle: Service> {
+        pub name: &'static str,
+        handle: Handle
+    };
+    backend = inventory,
+}; "a registry without options")]
+#[test_case::test_case("registry_keyed", quote::quote! {
+    Plugin<Handle: Service> {
+        pub name: &'static str,
+        pub depends_on: &'static [&'static str],
+        handle: Handle
+    };
+    key = name,
+    depends_on = depends_on,
+    error = Unavailable,
+    backend = inventory,
+}; "a fallible registry with keys and dependencies")]
+#[test_case::test_case("registry_serde", quote::quote! {
+    Plugin<Handle: Service> {
+        #[serde(rename = "id")]
+        pub name: &'static str,
+        handle: Handle
+    };
+    serde = true,
+    backend = inventory,
+}; "a registry serializing its metadata")]
+#[test_case::test_case("registry_clap", quote::quote! {
+    Plugin<Handle: Service> {
+        pub name: &'static str,
+        pub help: &'static str,
+        handle: Handle
+    };
+    clap = name,
+    help = help,
+    backend = inventory,
+}; "a registry of command line arguments")]
+#[test_case::test_case("registry_linkme", quote::quote! {
+    Plugin<Handle: Service> {
+        pub name: &'static str,
+        handle: Handle
+    };
+    backend = linkme,
+}; "a registry collected with linkme")]
+fn dyn_inventory(
+    name: &str,
+    tok: TokenStream,
+) {
+    snapshot(name, dyn_inventory_core::dyn_inventory(tok));
+}
+
+#[test]
+fn emit() {
+    snapshot(
+        "emit",
+        dyn_inventory_core::emit(quote::quote! {
+            pub Handle Service as Plugin {
+                name = "http",
+            };
+            backend = inventory,
+        }),
+    );
+}
+
+#[test]
+fn emit_factory() {
+    snapshot(
+        "emit_factory",
+        dyn_inventory_core::emit(quote::quote! {
+            Handle Service as Plugin {
+                name = "http",
+            };
+            factory = || Handle::new(),
+            backend = inventory,
+        }),
+    );
+}
+
+#[test]
+fn emit_linkme() {
+    snapshot(
+        "emit_linkme",
+        dyn_inventory_core::emit(quote::quote! {
+            Handle Service as Plugin {
+                name = "http",
+            };
+            backend = linkme,
+        }),
+    );
+}
+
+#[test]
+fn dyn_async() {
+    snapshot(
+        "dyn_async",
+        dyn_inventory_core::dyn_async(
+            quote::quote! {},
+            quote::quote! {
+                pub trait Pool {
+                    async fn size(&self) -> usize;
+
+                    async fn resize(&mut self, by: &usize) -> usize {
+                        self.size().await + by
+                    }
+                }
+            },
+        ),
+    );
+}
+
+#[test]
+fn parse_error() {
+    // errors are reported within the expansion, which is a single `compile_error!`
+    let expanded = dyn_inventory_core::dyn_inventory(quote::quote! {
+        Plugin<Handle: Service> {
+            handle: Handle
+        };
+        unknown = true,
+    });
+    assert_eq!(
+        expanded.to_string(),
+        quote::quote! { ::core::compile_error! { "unknown keyword" } }.to_string()
+    );
+}
@@ -0,0 +1,30 @@
+use std::{env, process::Command};
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=RUSTC");
+    if env::var_os("CARGO_FEATURE_DYLIB").is_none() {
+        return;
+    }
+
+    // the layout of rust types is only stable within a single compiler version, so shared
+    // libraries are checked against the version of rustc that built the host.
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".into());
+    let output = Command::new(&rustc)
+        .arg("-vV")
+        .output()
+        .unwrap_or_else(|err| panic!("could not run `{rustc} -vV`: {err}"));
+    let version = String::from_utf8(output.stdout)
+        .unwrap_or_else(|err| panic!("`{rustc} -vV` printed invalid utf-8: {err}"));
+    // an empty version would match every library, so the build fails instead
+    if !output.status.success() || !version.starts_with("rustc ") {
+        panic!(
+            "could not read the version of rustc from `{rustc} -vV`, which shared libraries are checked against: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    let version = version
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    println!("cargo:rustc-env=DYN_INVENTORY_RUSTC={version}");
+}
@@ -0,0 +1,122 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use semver::{Version, VersionReq};
+
+/// a plugin was built against an api version that is not compatible with the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiMismatch {
+    /// the api version declared by the registry
+    pub expected: &'static str,
+    /// the api version the plugin was built against, if any
+    pub found: Option<&'static str>,
+}
+
+impl core::fmt::Display for ApiMismatch {
+    fn fmt(
+        &self,
+        f: &mut core::fmt::Formatter<'_>,
+    ) -> core::fmt::Result {
+        match self.found {
+            Some(found) => {
+                write!(
+                    f,
+                    "built against api version {found}, which is not compatible with {}",
+                    self.expected
+                )
+            },
+            None => {
+                write!(
+                    f,
+                    "built without an api version, expected one compatible with {}",
+                    self.expected
+                )
+            },
+        }
+    }
+}
+
+impl core::error::Error for ApiMismatch {}
+
+/// checks that a plugin built against api version `found` can be used by a registry at api version
+/// `expected`.
+///
+/// versions are compared with cargo's caret rules: the registry must be a semver compatible
+/// upgrade of the version the plugin was built against. for example, a plugin built against `2.1`
+/// can be used by a registry at `2.3`, but not by one at `2.0` or `3.0`.
+pub fn check_api_version(
+    expected: &'static str,
+    found: Option<&'static str>,
+) -> Result<(), ApiMismatch> {
+    if compatible(expected, found) {
+        Ok(())
+    } else {
+        Err(ApiMismatch { expected, found })
+    }
+}
+
+/// whether `found` is compatible with `expected`, as [`check_api_version`] checks it, for versions
+/// which are not `'static`, such as those read from a shared library.
+pub(crate) fn compatible(
+    expected: &str,
+    found: Option<&str>,
+) -> bool {
+    let Some(found) = found else {
+        return false;
+    };
+
+    let requirement = VersionReq::parse(&format!("^{found}"));
+    let version = Version::parse(&complete(expected));
+    match (requirement, version) {
+        (Ok(requirement), Ok(version)) => requirement.matches(&version),
+        _ => false,
+    }
+}
+
+/// completes a partial version such as `2.3` to `2.3.0`. `dyn_inventory!` validates the declared
+/// api version with it too, so that what compiles is exactly what is compared at runtime.
+#[doc(hidden)]
+pub fn complete(version: &str) -> String {
+    match version.matches('.').count() {
+        0 => format!("{version}.0.0"),
+        1 => format!("{version}.0"),
+        _ => version.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::{check_api_version, complete};
+
+    #[test_case("2.3", Some("2.3"), true; "same version")]
+    #[test_case("2.3", Some("2.1"), true; "older minor")]
+    #[test_case("2.3.4", Some("2.3.1"), true; "older patch")]
+    #[test_case("2.3", Some("2.4"), false; "newer minor")]
+    #[test_case("2.3", Some("1.9"), false; "older major")]
+    #[test_case("3", Some("2.3"), false; "newer major")]
+    #[test_case("0.3.1", Some("0.3"), true; "same zero minor")]
+    #[test_case("0.4", Some("0.3"), false; "different zero minor")]
+    #[test_case("2.3", None, false; "unversioned")]
+    #[test_case("2.3", Some("two"), false; "invalid")]
+    fn compatibility(
+        expected: &'static str,
+        found: Option<&'static str>,
+        compatible: bool,
+    ) {
+        assert_eq!(check_api_version(expected, found).is_ok(), compatible);
+    }
+
+    #[test_case("2", "2.0.0"; "major")]
+    #[test_case("2.3", "2.3.0"; "minor")]
+    #[test_case("2.3.4", "2.3.4"; "patch")]
+    fn completes(
+        version: &str,
+        completed: &str,
+    ) {
+        assert_eq!(complete(version), completed);
+    }
+}
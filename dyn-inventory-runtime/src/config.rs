@@ -0,0 +1,433 @@
+//! configuration of plugins, for registries declared with `config = true`.
+//!
+//! each plugin is configured by the section of a [`Config`] under its key. the factory of a plugin
+//! declares the type of its configuration by taking it as an argument, and the section is
+//! deserialized into that type before the factory runs:
+//!
+//! ```rust,ignore
+//! #[derive(serde::Deserialize)]
+//! struct HttpConfig {
+//!     port: u16,
+//! }
+//!
+//! emit! {
+//!     Handle Service as Plugin {
+//!         name = "http",
+//!     };
+//!     factory = |config: HttpConfig| Handle { port: config.port },
+//! }
+//! ```
+//!
+//! factories which take no argument, and handles declared without a factory, ignore their section.
+
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+};
+
+use serde::de::DeserializeOwned;
+
+/// the configuration of every plugin of a registry, keyed by plugin name.
+///
+/// plugins without a section are configured from an empty table, so configurations whose fields
+/// all have defaults need no section at all.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    sections: BTreeMap<String, Section>,
+}
+
+impl Config {
+    /// the section of the plugin named `key`, or an empty table when there is none
+    pub fn section(
+        &self,
+        key: &str,
+    ) -> Section {
+        self.sections
+            .get(key)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// reports the first section which is not named by any of `keys`, the keys of the registered
+    /// plugins, so that a misspelled plugin name is not silently ignored
+    pub fn check<'a>(
+        &self,
+        keys: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), ConfigError> {
+        let keys: alloc::vec::Vec<&str> = keys.into_iter().collect();
+        match self
+            .sections
+            .keys()
+            .find(|name| !keys.contains(&name.as_str()))
+        {
+            Some(name) => Err(ConfigError::Unknown { name: name.clone() }),
+            None => Ok(()),
+        }
+    }
+
+    /// sets the section of the plugin named `key`
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        section: impl Into<Section>,
+    ) {
+        self.sections
+            .insert(key.into(), section.into());
+    }
+}
+
+/// objects are keyed by plugin name. any other value is a [`ConfigError::NotATable`].
+impl TryFrom<serde_json::Value> for Config {
+    type Error = ConfigError;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, ConfigError> {
+        let found = match value {
+            serde_json::Value::Object(sections) => return Ok(sections.into()),
+            serde_json::Value::Null => "null",
+            serde_json::Value::Bool(_) => "boolean",
+            serde_json::Value::Number(_) => "number",
+            serde_json::Value::String(_) => "string",
+            serde_json::Value::Array(_) => "array",
+        };
+        Err(ConfigError::NotATable { found })
+    }
+}
+
+impl From<serde_json::Map<String, serde_json::Value>> for Config {
+    fn from(sections: serde_json::Map<String, serde_json::Value>) -> Self {
+        Self {
+            sections: sections
+                .into_iter()
+                .map(|(key, section)| (key, section.into()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::Table> for Config {
+    fn from(sections: toml::Table) -> Self {
+        Self {
+            sections: sections
+                .into_iter()
+                .map(|(key, section)| (key, section.into()))
+                .collect(),
+        }
+    }
+}
+
+/// the configuration of one plugin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section(Repr);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Repr {
+    Json(serde_json::Value),
+    #[cfg(feature = "toml")]
+    Toml(toml::Value),
+}
+
+impl Default for Section {
+    /// an empty table
+    fn default() -> Self {
+        Self(Repr::Json(serde_json::Value::Object(Default::default())))
+    }
+}
+
+impl From<serde_json::Value> for Section {
+    fn from(value: serde_json::Value) -> Self {
+        Self(Repr::Json(value))
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::Value> for Section {
+    fn from(value: toml::Value) -> Self {
+        Self(Repr::Toml(value))
+    }
+}
+
+/// why a section could not be deserialized, before the plugin it configures is known
+struct Invalid {
+    field: Option<String>,
+    message: String,
+}
+
+impl<E: serde::de::Error> From<serde_path_to_error::Error<E>> for Invalid {
+    fn from(err: serde_path_to_error::Error<E>) -> Self {
+        let field = err
+            .path()
+            .iter()
+            .next()
+            .is_some()
+            .then(|| err.path().to_string());
+        Self {
+            field,
+            message: err.into_inner().to_string(),
+        }
+    }
+}
+
+impl Section {
+    fn deserialize<C: DeserializeOwned>(&self) -> Result<C, Invalid> {
+        match &self.0 {
+            Repr::Json(value) => Ok(serde_path_to_error::deserialize(value)?),
+            #[cfg(feature = "toml")]
+            Repr::Toml(value) => Ok(serde_path_to_error::deserialize(value.clone())?),
+        }
+    }
+}
+
+/// a plugin could not be configured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// the section of a plugin does not match the configuration its factory takes
+    Invalid {
+        /// the key of the plugin
+        plugin: &'static str,
+        /// the path of the offending field within the section, such as `tls.cert`, when the error
+        /// is not about the section as a whole. missing fields are named by `message`.
+        field: Option<String>,
+        /// what is wrong with the section
+        message: String,
+    },
+    /// the configuration has a section under `name`, but no plugin is registered under it
+    Unknown { name: String },
+    /// the configuration is not a table of sections, but the kind of value `found`
+    NotATable { found: &'static str },
+}
+
+impl core::fmt::Display for ConfigError {
+    fn fmt(
+        &self,
+        f: &mut core::fmt::Formatter<'_>,
+    ) -> core::fmt::Result {
+        match self {
+            Self::Invalid {
+                plugin,
+                field: Some(field),
+                message,
+            } => {
+                write!(
+                    f,
+                    "invalid configuration of plugin `{plugin}` at `{field}`: {message}"
+                )
+            },
+            Self::Invalid {
+                plugin,
+                field: None,
+                message,
+            } => write!(f, "invalid configuration of plugin `{plugin}`: {message}"),
+            Self::Unknown { name } => {
+                write!(
+                    f,
+                    "the configuration has a section for `{name}`, which is not a registered plugin"
+                )
+            },
+            Self::NotATable { found } => {
+                write!(
+                    f,
+                    "the configuration must be a table of sections, found a {found}"
+                )
+            },
+        }
+    }
+}
+
+impl core::error::Error for ConfigError {}
+
+/// for configurations which always convert, such as `toml::Table`
+impl From<core::convert::Infallible> for ConfigError {
+    fn from(never: core::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
+type Configure<T> = Box<dyn FnOnce(&Section) -> Result<Box<dyn FnOnce() -> T>, Invalid>>;
+
+/// the factory of a plugin of a registry declared with `config = true`, which deserializes the
+/// section of the plugin before it runs.
+pub struct Configurable<T> {
+    configure: Configure<T>,
+}
+
+impl<T: 'static> Configurable<T> {
+    /// a factory which ignores its section
+    pub fn new(factory: impl FnOnce() -> T + 'static) -> Self {
+        Self {
+            configure: Box::new(|_| Ok(Box::new(factory))),
+        }
+    }
+
+    /// converts the output of the factory with `f`
+    pub fn map<U: 'static>(
+        self,
+        f: impl FnOnce(T) -> U + 'static,
+    ) -> Configurable<U> {
+        Configurable {
+            configure: Box::new(move |section| {
+                let factory = (self.configure)(section)?;
+                Ok(Box::new(move || f(factory())))
+            }),
+        }
+    }
+
+    /// deserializes `section`, the configuration of `plugin`, and returns the factory to run with it
+    pub fn configure(
+        self,
+        plugin: &'static str,
+        section: &Section,
+    ) -> Result<Box<dyn FnOnce() -> T>, ConfigError> {
+        (self.configure)(section).map_err(|Invalid { field, message }| {
+            ConfigError::Invalid {
+                plugin,
+                field,
+                message,
+            }
+        })
+    }
+}
+
+/// a factory passed to `emit!`, which takes either no argument or its configuration. `Args` is
+/// the tuple of arguments it takes.
+pub trait Factory<Args, T> {
+    fn into_configurable(self) -> Configurable<T>;
+}
+
+impl<T: 'static, F: FnOnce() -> T + 'static> Factory<(), T> for F {
+    fn into_configurable(self) -> Configurable<T> {
+        Configurable::new(self)
+    }
+}
+
+impl<C: DeserializeOwned + 'static, T: 'static, F: FnOnce(C) -> T + 'static> Factory<(C,), T>
+    for F
+{
+    fn into_configurable(self) -> Configurable<T> {
+        Configurable {
+            configure: Box::new(|section| {
+                let config = section.deserialize::<C>()?;
+                Ok(Box::new(move || self(config)))
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Http {
+        port: u16,
+        #[serde(default)]
+        tls: Option<Tls>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Tls {
+        cert: String,
+    }
+
+    fn configure(section: serde_json::Value) -> Result<Http, ConfigError> {
+        let factory = (|config: Http| config).into_configurable();
+        factory
+            .configure("http", &section.into())
+            .map(|factory| factory())
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(
+            configure(json!({ "port": 80, "tls": { "cert": "a.pem" } })).unwrap(),
+            Http {
+                port: 80,
+                tls: Some(Tls {
+                    cert: "a.pem".into()
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_field() {
+        let err = configure(json!({ "port": 80, "tls": { "cert": 1 } })).unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::Invalid { field: Some(field), .. } if field == "tls.cert")
+        );
+        assert_eq!(
+            err.to_string(),
+            "invalid configuration of plugin `http` at `tls.cert`: invalid type: integer `1`, expected a string"
+        );
+    }
+
+    #[test]
+    fn missing_field() {
+        let err = configure(json!({})).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { field: None, .. }));
+        assert_eq!(
+            err.to_string(),
+            "invalid configuration of plugin `http`: missing field `port`"
+        );
+    }
+
+    #[test]
+    fn sections() {
+        let config = Config::try_from(json!({ "http": { "port": 80 } })).unwrap();
+        assert_eq!(config.section("http"), json!({ "port": 80 }).into());
+        assert_eq!(config.section("metrics"), Section::default());
+
+        // factories without an argument ignore their section
+        let factory = (|| 1).into_configurable();
+        assert_eq!(
+            factory
+                .configure("one", &json!(null).into())
+                .unwrap()(),
+            1
+        );
+    }
+
+    #[test]
+    fn not_a_table() {
+        let err = Config::try_from(json!(null)).unwrap_err();
+        assert_eq!(err, ConfigError::NotATable { found: "null" });
+        assert_eq!(
+            err.to_string(),
+            "the configuration must be a table of sections, found a null"
+        );
+        assert_eq!(
+            Config::try_from(json!([1])).unwrap_err(),
+            ConfigError::NotATable { found: "array" }
+        );
+    }
+
+    #[test]
+    fn unknown_section() {
+        let config = Config::try_from(json!({ "http": {}, "htp": {} })).unwrap();
+        assert_eq!(
+            config.check(["http", "metrics"]),
+            Err(ConfigError::Unknown { name: "htp".into() })
+        );
+        assert_eq!(config.check(["http", "htp"]), Ok(()));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml() {
+        let config: toml::Table =
+            toml::from_str("[http]\nport = 80\n\n[http.tls]\ncert = 1\n").unwrap();
+        let config = Config::from(config);
+        let factory = (|config: Http| config).into_configurable();
+        let err = factory
+            .configure("http", &config.section("http"))
+            .err()
+            .unwrap();
+        assert!(
+            matches!(err, ConfigError::Invalid { field: Some(field), .. } if field == "tls.cert")
+        );
+    }
+}
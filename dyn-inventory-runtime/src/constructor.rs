@@ -0,0 +1,68 @@
+use alloc::sync::Arc;
+
+/// how a registration builds its handle, held by the generated `StructNameInit` and `StructName`.
+///
+/// registrations submitted at link time hold a function, as they are built in statics. those built
+/// at runtime, with `mock_plugin!`, hold a shared closure, which may capture state.
+pub struct Constructor<T>(Repr<T>);
+
+enum Repr<T> {
+    Fn(fn() -> T),
+    Shared(Arc<dyn Fn() -> T + Send + Sync>),
+}
+
+impl<T> Constructor<T> {
+    /// a constructor calling `get`, which can be built in a static
+    pub const fn new(get: fn() -> T) -> Self {
+        Self(Repr::Fn(get))
+    }
+
+    /// a constructor calling `get`, which is shared by every clone of the registration
+    pub fn shared(get: impl Fn() -> T + Send + Sync + 'static) -> Self {
+        Self(Repr::Shared(Arc::new(get)))
+    }
+
+    /// builds a new value
+    pub fn call(&self) -> T {
+        match &self.0 {
+            Repr::Fn(get) => get(),
+            Repr::Shared(get) => get(),
+        }
+    }
+}
+
+impl<T> Clone for Constructor<T> {
+    fn clone(&self) -> Self {
+        match &self.0 {
+            Repr::Fn(get) => Self(Repr::Fn(*get)),
+            Repr::Shared(get) => Self(Repr::Shared(get.clone())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn shared_between_clones() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let constructor = Constructor::shared({
+            let calls = calls.clone();
+            move || calls.fetch_add(1, Ordering::Relaxed)
+        });
+        let cloned = constructor.clone();
+
+        assert_eq!(constructor.call(), 0);
+        assert_eq!(cloned.call(), 1);
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn in_a_static() {
+        static CONSTRUCTOR: Constructor<u8> = Constructor::new(|| 1);
+        assert_eq!(CONSTRUCTOR.clone().call(), 1);
+    }
+}
@@ -0,0 +1,429 @@
+use std::{
+    any::type_name,
+    env::consts::DLL_SUFFIX,
+    ffi::OsStr,
+    fs, io,
+    mem::{align_of, size_of},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+
+/// the version of the layout of [`Table`]. bumped whenever the layout changes.
+pub const ABI_VERSION: u32 = 2;
+
+/// the version of rustc this crate was built with.
+pub const RUSTC_VERSION: &str = env!("DYN_INVENTORY_RUSTC");
+
+/// a string which can be passed across the library boundary.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RawStr {
+    ptr: *const u8,
+    len: usize,
+}
+
+impl RawStr {
+    pub const fn new(value: &'static str) -> Self {
+        Self {
+            ptr: value.as_ptr(),
+            len: value.len(),
+        }
+    }
+
+    /// the string, borrowed for as long as `library`, which it was read from, is loaded.
+    ///
+    /// # Safety
+    ///
+    /// the string must have been created with [`RawStr::new`] in `library`.
+    unsafe fn as_str<'a>(
+        &self,
+        _library: &'a libloading::Library,
+    ) -> &'a str {
+        // SAFETY: created from a `&'static str` of the library, which is valid while it is loaded
+        unsafe { std::str::from_utf8_unchecked(std::slice::from_raw_parts(self.ptr, self.len)) }
+    }
+}
+
+/// the registration table exported by a shared library built with `dyn_inventory::export!`.
+///
+/// the table itself only uses ffi-safe types, so the host can always read it and check that it was
+/// built for the same registry, by the same compiler, before reading the registrations it points to.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Table {
+    /// [`ABI_VERSION`] of the library
+    pub abi: u32,
+    /// [`RUSTC_VERSION`] of the library
+    pub rustc: RawStr,
+    /// the identity of the registry the library was built against
+    pub registry: RawStr,
+    /// the api version of the registry the library was built against, empty when it declares none
+    pub api_version: RawStr,
+    /// the size of each registration
+    pub size: usize,
+    /// the alignment of each registration
+    pub align: usize,
+    entries: *const (),
+    len: usize,
+}
+
+impl Table {
+    /// exports `entries`, the registrations of `registry` at `api_version`.
+    pub fn new<T>(
+        registry: &'static str,
+        api_version: Option<&'static str>,
+        entries: &'static [T],
+    ) -> Self {
+        Self {
+            abi: ABI_VERSION,
+            rustc: RawStr::new(RUSTC_VERSION),
+            registry: RawStr::new(registry),
+            api_version: RawStr::new(api_version.unwrap_or_default()),
+            size: size_of::<T>(),
+            align: align_of::<T>(),
+            entries: entries.as_ptr().cast(),
+            len: entries.len(),
+        }
+    }
+}
+
+/// a shared library could not be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// the library, or the directory it was read from, could not be read
+    Read { path: PathBuf, source: io::Error },
+    /// the library could not be opened
+    Open {
+        path: PathBuf,
+        source: libloading::Error,
+    },
+    /// the library does not export the registration table of the registry
+    Missing { path: PathBuf, symbol: String },
+    /// the library was built against a different abi, compiler or registry than the host, so its
+    /// registrations can not be read
+    Abi {
+        path: PathBuf,
+        field: &'static str,
+        expected: String,
+        found: String,
+    },
+    /// reloading the libraries in `dir` would load more copies of them than the reloader's limit
+    Limit { dir: PathBuf, limit: usize },
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            Self::Read { path, source } => write!(f, "could not read {}: {source}", path.display()),
+            Self::Open { path, source } => write!(f, "could not open {}: {source}", path.display()),
+            Self::Missing { path, symbol } => {
+                write!(f, "{} does not export `{symbol}`", path.display())
+            },
+            Self::Abi {
+                path,
+                field,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "{} was built with {field} `{found}`, expected `{expected}`",
+                    path.display()
+                )
+            },
+            Self::Limit { dir, limit } => {
+                write!(
+                    f,
+                    "reloading {} would load more than {limit} copies of its libraries",
+                    dir.display()
+                )
+            },
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Read { source, .. } => Some(source),
+            Self::Open { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// a loaded shared library.
+///
+/// registrations read from a library point into it: their `&'static str` and other `&'static`
+/// fields, their [`Source`](crate::Source), and the code of their factories and handles. the host
+/// uses all of these as `'static`, and safe code can copy them out of any plugin, registration or
+/// error. so once [`Library::entries`] has returned registrations, the library is never unloaded,
+/// and stays mapped until the process exits, even once every clone is dropped. libraries whose
+/// registrations were never read, such as those rejected by the checks of `entries`, are unloaded
+/// once every clone is dropped.
+#[derive(Clone)]
+pub struct Library {
+    path: Arc<Path>,
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    library: Option<libloading::Library>,
+    /// the private copy the library was opened from, removed once every clone is dropped
+    copy: Option<PathBuf>,
+    /// whether registrations were read from the library, which then must never be unloaded
+    pinned: AtomicBool,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        let library = self.library.take();
+        if self.pinned.load(Ordering::Acquire) {
+            // registrations read from the library may still be in use as `'static`
+            std::mem::forget(library);
+        } else {
+            drop(library);
+        }
+        // the copy can be removed while it is mapped on unix. elsewhere, it is left behind.
+        if let Some(copy) = &self.copy {
+            let _ = fs::remove_file(copy);
+        }
+    }
+}
+
+impl std::fmt::Debug for Library {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("Library")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Library {
+    /// opens the shared library at `path`.
+    ///
+    /// # Safety
+    ///
+    /// opening a library runs its initializers, which may do anything. see
+    /// [`libloading::Library::new`]. the file must not be modified in place while it is loaded,
+    /// which is for the rest of the process once registrations are read from it. replace it with a
+    /// new file instead, or open it with [`Library::open_copy`].
+    pub unsafe fn open(path: impl AsRef<OsStr>) -> Result<Self, LoadError> {
+        let path = Path::new(path.as_ref());
+        // SAFETY: upheld by the caller
+        let inner = unsafe { libloading::Library::new(path) }.map_err(|source| {
+            LoadError::Open {
+                path: path.into(),
+                source,
+            }
+        })?;
+        Ok(Self {
+            path: path.into(),
+            inner: Arc::new(Inner {
+                library: Some(inner),
+                copy: None,
+                pinned: AtomicBool::new(false),
+            }),
+        })
+    }
+
+    /// opens a private copy of the shared library at `path`.
+    ///
+    /// the platform loader returns the library which is already loaded when the same path is opened
+    /// twice, so a library which changed on disk is only loaded anew once every previous version
+    /// was unloaded. opening a copy loads the current version regardless. the copy is removed once
+    /// the library is unloaded.
+    ///
+    /// # Safety
+    ///
+    /// see [`Library::open`].
+    pub unsafe fn open_copy(path: impl AsRef<OsStr>) -> Result<Self, LoadError> {
+        static COPIES: AtomicU64 = AtomicU64::new(0);
+
+        let path = Path::new(path.as_ref());
+        let name = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy();
+        let copy = std::env::temp_dir().join(format!(
+            "dyn-inventory-{}-{}-{name}",
+            process::id(),
+            COPIES.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::copy(path, &copy).map_err(|source| {
+            LoadError::Read {
+                path: path.into(),
+                source,
+            }
+        })?;
+
+        // SAFETY: upheld by the caller
+        let inner = match unsafe { libloading::Library::new(&copy) } {
+            Ok(inner) => inner,
+            Err(source) => {
+                let _ = fs::remove_file(&copy);
+                return Err(LoadError::Open {
+                    path: path.into(),
+                    source,
+                });
+            },
+        };
+        Ok(Self {
+            path: path.into(),
+            inner: Arc::new(Inner {
+                library: Some(inner),
+                copy: Some(copy),
+                pinned: AtomicBool::new(false),
+            }),
+        })
+    }
+
+    /// the path the library was opened from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// reads the registrations of type `T` exported under `symbol`, after checking that the table
+    /// was built for `registry` with the same abi and compiler as the host.
+    ///
+    /// the registrations are read even when the library was built against an api version which is
+    /// not compatible with `api_version`, so that the caller can report them, as long as their
+    /// layout matches. when it does not, the api versions are reported instead of the layout.
+    ///
+    /// once registrations are returned, the library is never unloaded, so they, and everything
+    /// they point to, are valid for `'static`.
+    ///
+    /// # Safety
+    ///
+    /// `symbol`, if the library exports it, must name a function exported by
+    /// `dyn_inventory::export!`. `registry` must uniquely identify `T`: the library must have
+    /// been built against a version of the crate declaring `T` which declares `T` as the host
+    /// does, as only its name, module and size are checked.
+    pub unsafe fn entries<T>(
+        &self,
+        symbol: &str,
+        registry: &'static str,
+        api_version: Option<&str>,
+    ) -> Result<&'static [T], LoadError> {
+        // SAFETY: upheld by the caller
+        let library = self
+            .inner
+            .library
+            .as_ref()
+            .expect("the library is loaded");
+        let export = unsafe { library.get::<extern "C" fn() -> Table>(symbol.as_bytes()) }
+            .map_err(|_| {
+                LoadError::Missing {
+                    path: self.path.to_path_buf(),
+                    symbol: symbol.into(),
+                }
+            })?;
+        let table = export();
+
+        let mismatch = |field, expected: &dyn ToString, found: &dyn ToString| {
+            LoadError::Abi {
+                path: self.path.to_path_buf(),
+                field,
+                expected: expected.to_string(),
+                found: found.to_string(),
+            }
+        };
+        if table.abi != ABI_VERSION {
+            return Err(mismatch("abi version", &ABI_VERSION, &table.abi));
+        }
+        // SAFETY: the abi versions match, so the table was built by `Table::new`
+        let rustc = unsafe { table.rustc.as_str(library) };
+        if rustc != RUSTC_VERSION {
+            return Err(mismatch("rustc", &RUSTC_VERSION, &rustc));
+        }
+        // SAFETY: as above
+        let found = unsafe { table.registry.as_str(library) };
+        if found != registry {
+            return Err(mismatch("registry", &registry, &found));
+        }
+        if table.size != size_of::<T>() || table.align != align_of::<T>() {
+            // SAFETY: as above
+            let found = unsafe { table.api_version.as_str(library) };
+            let found = Some(found).filter(|found| !found.is_empty());
+            if let Some(expected) = api_version
+                && !crate::api::compatible(expected, found)
+            {
+                return Err(mismatch("api version", &expected, &found.unwrap_or("none")));
+            }
+            return Err(mismatch(
+                "layout",
+                &format!(
+                    "{} (size {}, align {})",
+                    type_name::<T>(),
+                    size_of::<T>(),
+                    align_of::<T>()
+                ),
+                &format!("size {}, align {}", table.size, table.align),
+            ));
+        }
+
+        if table.len == 0 {
+            return Ok(&[]);
+        }
+        self.inner
+            .pinned
+            .store(true, Ordering::Release);
+        // SAFETY: the table was built by `Table::new` for `T`, by the same compiler, and the library
+        // is never unloaded now that it is pinned
+        Ok(unsafe { std::slice::from_raw_parts(table.entries.cast::<T>(), table.len) })
+    }
+}
+
+/// a shared library found by [`scan`], and when it was last modified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryFile {
+    pub path: PathBuf,
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// lists the shared libraries in `dir`, by name. a change to any of them, or to which libraries
+/// are present, changes the result.
+pub fn scan(dir: impl AsRef<Path>) -> Result<Vec<LibraryFile>, LoadError> {
+    let dir = dir.as_ref();
+    let read = |source| {
+        LoadError::Read {
+            path: dir.into(),
+            source,
+        }
+    };
+
+    let mut libraries = vec![];
+    for entry in fs::read_dir(dir).map_err(read)? {
+        let entry = entry.map_err(read)?;
+        let path = entry.path();
+        if !path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().ends_with(DLL_SUFFIX))
+        {
+            continue;
+        }
+        let metadata = entry.metadata().map_err(read)?;
+        if !metadata.is_file() {
+            continue;
+        }
+        libraries.push(LibraryFile {
+            path,
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        });
+    }
+    libraries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(libraries)
+}
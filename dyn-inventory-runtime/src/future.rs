@@ -0,0 +1,62 @@
+use alloc::boxed::Box;
+use core::{
+    future::Future,
+    pin::{Pin, pin},
+    task::{Context, Poll, Waker},
+};
+#[cfg(feature = "std")]
+use std::{
+    sync::Arc,
+    task::Wake,
+    thread::{self, Thread},
+};
+
+/// the future returned by async trait methods rewritten with `#[dyn_async]`, and by the factories of
+/// registries declared with `async_factory = true`.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// the future returned by async trait methods rewritten with `#[dyn_async(?Send)]`.
+pub type LocalBoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+#[cfg(feature = "std")]
+struct ThreadWaker(Thread);
+
+#[cfg(feature = "std")]
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// drives a future to completion on the current thread.
+///
+/// this is a minimal executor used by the blocking constructors of async registries. it does not
+/// provide a reactor, so futures which depend on a particular runtime (such as tokio's io or timers)
+/// must be awaited within that runtime instead, i.e. through `StructNameCollector::new_async()`.
+#[cfg(feature = "std")]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// drives a future to completion on the current thread.
+///
+/// without the `std` feature there is no thread to park, so the future is polled until it is ready.
+#[cfg(not(feature = "std"))]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => core::hint::spin_loop(),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! runtime traits implemented by every registry generated with
+//! [`dyn-inventory`](https://docs.rs/dyn-inventory).
+//!
+//! each `dyn_inventory!` declaration produces its own concrete types. these traits allow tooling to
+//! be written once for any registry:
+//!
+//! ```rust,ignore
+//! use dyn_inventory_runtime::{Entry, Registry};
+//!
+//! fn count<R: Registry>() -> usize {
+//!     R::collect().len()
+//! }
+//!
+//! assert_eq!(count::<PluginCollector>(), 1);
+//! ```
+//!
+//! without the default `std` feature, this crate and the registries generated against it only
+//! require `alloc`.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// generated code refers to `alloc` and `std` through this crate, as `alloc` is only in the extern
+// prelude of crates which declare it, and `std` is not in the extern prelude of `no_std` crates
+#[doc(hidden)]
+pub extern crate alloc;
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub extern crate std;
+
+mod api;
+#[cfg(feature = "config")]
+pub mod config;
+mod constructor;
+#[cfg(feature = "dylib")]
+mod dylib;
+mod future;
+mod lifecycle;
+#[cfg(feature = "std")]
+mod panic;
+mod sync;
+pub mod trace;
+
+use alloc::vec::Vec;
+
+pub use api::{ApiMismatch, check_api_version, complete};
+pub use constructor::Constructor;
+#[cfg(feature = "dylib")]
+pub use dylib::{ABI_VERSION, Library, LibraryFile, LoadError, RUSTC_VERSION, RawStr, Table, scan};
+pub use future::{BoxFuture, LocalBoxFuture, block_on};
+pub use lifecycle::{HookError, Lifecycle, StartError};
+#[cfg(feature = "std")]
+pub use panic::{CatchUnwind, Panic, catch_unwind};
+pub use sync::RwLock;
+
+/// a registry of plugins, implemented by the generated `StructNameCollector`.
+pub trait Registry {
+    /// the collected plugin, `StructName`
+    type Entry: Entry<Handle = Self::Handle>;
+
+    /// the link-time registration, `StructNameInit`
+    type Init;
+
+    /// the trait object each plugin is used through, `dyn TraitName`
+    type Handle: ?Sized;
+
+    /// why the registered plugins could not be collected: `StructNameDependencyError` for
+    /// registries declared with `depends_on`, [`Infallible`](core::convert::Infallible) otherwise
+    type Error: core::error::Error;
+
+    /// instantiates every registered plugin, or reports why they cannot be ordered
+    fn try_collect() -> Result<Vec<Self::Entry>, Self::Error>;
+
+    /// instantiates every registered plugin.
+    ///
+    /// # Panics
+    ///
+    /// when [`try_collect`](Self::try_collect) fails, such as on a missing dependency or a
+    /// dependency cycle.
+    fn collect() -> Vec<Self::Entry> {
+        match Self::try_collect() {
+            Ok(plugins) => plugins,
+            Err(err) => panic!("{err}"),
+        }
+    }
+}
+
+/// a collected plugin, implemented by the generated `StructName`.
+pub trait Entry {
+    /// the trait object the plugin is used through, `dyn TraitName`
+    type Handle: ?Sized;
+
+    /// the instantiated plugin
+    fn handle(&self) -> &Self::Handle;
+
+    /// where the plugin was registered
+    fn source(&self) -> &Source;
+}
+
+/// where a plugin was registered, captured by `emit!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Source {
+    /// `module_path!()` of the `emit!` invocation
+    pub module_path: &'static str,
+    /// `file!()` of the `emit!` invocation
+    pub file: &'static str,
+    /// `line!()` of the `emit!` invocation
+    pub line: u32,
+    /// the name of the crate that registered the plugin
+    pub package: &'static str,
+    /// the version of the crate that registered the plugin
+    pub version: &'static str,
+}
+
+impl core::fmt::Display for Source {
+    fn fmt(
+        &self,
+        f: &mut core::fmt::Formatter<'_>,
+    ) -> core::fmt::Result {
+        write!(
+            f,
+            "`{}` ({} {}, {}:{})",
+            self.module_path, self.package, self.version, self.file, self.line
+        )
+    }
+}
+
+/// captures the [`Source`] of the invocation site.
+#[macro_export]
+macro_rules! source {
+    () => {
+        $crate::Source {
+            module_path: ::core::module_path!(),
+            file: ::core::file!(),
+            line: ::core::line!(),
+            package: ::core::env!("CARGO_PKG_NAME"),
+            version: ::core::env!("CARGO_PKG_VERSION"),
+        }
+    };
+}
@@ -0,0 +1,58 @@
+use alloc::boxed::Box;
+
+use crate::Source;
+
+/// the error a plugin fails to start with.
+pub type HookError = Box<dyn core::error::Error + Send + Sync>;
+
+/// hooks run when the plugins of a registry declared with `lifecycle = true` are started and
+/// stopped, through `StructNameCollector::start()`.
+///
+/// the trait of the registry must declare `Lifecycle` as a supertrait. plugins are started in the
+/// order they were collected, which respects `depends_on` and `priority`, and stopped in reverse.
+pub trait Lifecycle {
+    /// acquires the resources of the plugin, before it is used
+    fn on_load(&mut self) -> Result<(), HookError> {
+        Ok(())
+    }
+
+    /// releases the resources of the plugin. only called for plugins which started.
+    fn on_unload(&mut self) {}
+}
+
+/// a plugin failed to start. the plugins started before it have been stopped again.
+#[derive(Debug)]
+pub struct StartError {
+    /// the key of the plugin, for keyed registries
+    pub key: Option<&'static str>,
+    /// where the plugin was registered
+    pub source: Source,
+    /// the error `on_load` returned
+    pub error: HookError,
+    /// how many plugins had started, and were stopped again
+    pub stopped: usize,
+}
+
+impl core::fmt::Display for StartError {
+    fn fmt(
+        &self,
+        f: &mut core::fmt::Formatter<'_>,
+    ) -> core::fmt::Result {
+        match self.key {
+            Some(key) => write!(f, "plugin `{key}` failed to start: {}", self.error),
+            None => {
+                write!(
+                    f,
+                    "plugin registered by {} failed to start: {}",
+                    self.source, self.error
+                )
+            },
+        }
+    }
+}
+
+impl core::error::Error for StartError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&*self.error)
+    }
+}
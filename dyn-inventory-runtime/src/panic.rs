@@ -0,0 +1,113 @@
+use std::{
+    any::Any,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// a plugin's factory panicked while it was being instantiated.
+pub struct Panic {
+    /// the value the factory panicked with
+    pub payload: Box<dyn Any + Send>,
+}
+
+impl Panic {
+    /// the panic message, when the factory panicked with a string (as `panic!` does)
+    pub fn message(&self) -> Option<&str> {
+        if let Some(message) = self.payload.downcast_ref::<&'static str>() {
+            Some(message)
+        } else {
+            self.payload
+                .downcast_ref::<String>()
+                .map(String::as_str)
+        }
+    }
+}
+
+impl std::fmt::Debug for Panic {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("Panic")
+            .field("message", &self.message())
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Display for Panic {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self.message() {
+            Some(message) => write!(f, "panicked: {message}"),
+            None => write!(f, "panicked"),
+        }
+    }
+}
+
+impl std::error::Error for Panic {}
+
+/// calls `f`, catching a panic instead of unwinding past the caller.
+///
+/// used by the collectors of registries declared with `catch_unwind = true`. the panic hook still
+/// runs, so the panic is reported as usual before it is caught.
+pub fn catch_unwind<T>(f: impl FnOnce() -> T) -> Result<T, Panic> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| Panic { payload })
+}
+
+/// a future which catches a panic while it is polled, the async counterpart of [`catch_unwind`].
+pub struct CatchUnwind<F> {
+    future: F,
+}
+
+impl<F: Future + Unpin> CatchUnwind<F> {
+    pub fn new(future: F) -> Self {
+        Self { future }
+    }
+}
+
+impl<F: Future + Unpin> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, Panic>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        match catch_unwind(|| Pin::new(&mut self.future).poll(cx)) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(panic) => Poll::Ready(Err(panic)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::{CatchUnwind, catch_unwind};
+    use crate::block_on;
+
+    #[test_case(|| panic!("static"), Some("static"); "str message")]
+    #[test_case(|| panic!("formatted {}", 1), Some("formatted 1"); "string message")]
+    #[test_case(|| std::panic::panic_any(1u8), None; "other payload")]
+    fn message(
+        f: fn(),
+        message: Option<&str>,
+    ) {
+        let panic = catch_unwind(f).unwrap_err();
+        assert_eq!(panic.message(), message);
+    }
+
+    #[test]
+    fn future() {
+        let caught = block_on(CatchUnwind::new(Box::pin(async { panic!("async") })));
+        assert_eq!(caught.unwrap_err().message(), Some("async"));
+
+        let ok = block_on(CatchUnwind::new(Box::pin(async { 1 })));
+        assert_eq!(ok.unwrap(), 1);
+    }
+}
@@ -0,0 +1,155 @@
+#[cfg(feature = "std")]
+use core::ops::{Deref, DerefMut};
+
+/// a reader-writer lock which ignores poisoning, used by the generated `StructNameRegistry`.
+///
+/// with the `std` feature, this is [`std::sync::RwLock`]. without it, there are no threads to park,
+/// so the lock spins, and readers hold it exclusively as writers do.
+pub struct RwLock<T> {
+    #[cfg(feature = "std")]
+    inner: std::sync::RwLock<T>,
+    #[cfg(not(feature = "std"))]
+    locked: core::sync::atomic::AtomicBool,
+    #[cfg(not(feature = "std"))]
+    value: core::cell::UnsafeCell<T>,
+}
+
+#[cfg(feature = "std")]
+impl<T> RwLock<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            inner: std::sync::RwLock::new(value),
+        }
+    }
+
+    /// locks for reading, blocking while the lock is held for writing
+    pub fn read(&self) -> impl Deref<Target = T> + '_ {
+        self.inner
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// locks for writing, blocking while the lock is held
+    pub fn write(&self) -> impl DerefMut<Target = T> + '_ {
+        self.inner
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod spin {
+    use core::{
+        cell::UnsafeCell,
+        ops::{Deref, DerefMut},
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    use super::RwLock;
+
+    // SAFETY: the value is only accessed through a guard, and only one guard exists at a time
+    unsafe impl<T: Send> Send for RwLock<T> {}
+    // SAFETY: as above
+    unsafe impl<T: Send + Sync> Sync for RwLock<T> {}
+
+    pub struct Guard<'a, T> {
+        lock: &'a RwLock<T>,
+    }
+
+    impl<T> Deref for Guard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            // SAFETY: the guard holds the lock
+            unsafe { &*self.lock.value.get() }
+        }
+    }
+
+    impl<T> DerefMut for Guard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            // SAFETY: the guard holds the lock
+            unsafe { &mut *self.lock.value.get() }
+        }
+    }
+
+    impl<T> Drop for Guard<'_, T> {
+        fn drop(&mut self) {
+            self.lock
+                .locked
+                .store(false, Ordering::Release);
+        }
+    }
+
+    impl<T> RwLock<T> {
+        pub const fn new(value: T) -> Self {
+            Self {
+                locked: AtomicBool::new(false),
+                value: UnsafeCell::new(value),
+            }
+        }
+
+        fn lock(&self) -> Guard<'_, T> {
+            while self
+                .locked
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                core::hint::spin_loop();
+            }
+            Guard { lock: self }
+        }
+
+        /// locks for reading, spinning while the lock is held
+        pub fn read(&self) -> impl Deref<Target = T> + '_ {
+            self.lock()
+        }
+
+        /// locks for writing, spinning while the lock is held
+        pub fn write(&self) -> impl DerefMut<Target = T> + '_ {
+            self.lock()
+        }
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for RwLock<T> {
+    fn fmt(
+        &self,
+        f: &mut core::fmt::Formatter<'_>,
+    ) -> core::fmt::Result {
+        f.debug_struct("RwLock")
+            .field("value", &*self.read())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RwLock;
+
+    #[test]
+    fn read_write() {
+        let lock = RwLock::new(alloc::vec![1]);
+        lock.write().push(2);
+        assert_eq!(*lock.read(), [1, 2]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn ignores_poisoning() {
+        let lock = std::sync::Arc::new(RwLock::new(1));
+        let poisoned = lock.clone();
+        std::thread::spawn(move || {
+            let _guard = poisoned.write();
+            panic!("poison");
+        })
+        .join()
+        .unwrap_err();
+        assert_eq!(*lock.read(), 1);
+    }
+}
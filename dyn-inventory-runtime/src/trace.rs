@@ -0,0 +1,196 @@
+//! instrumentation of collection, used by every generated collector.
+//!
+//! with the `tracing` feature, collecting a registry is recorded with
+//! [`tracing`](https://docs.rs/tracing), under the target `dyn_inventory`:
+//!
+//! - a `collect` span for each collection, with the `registry` it collects. when it closes, an
+//!   event records how many plugins were `registered`, `collected` and `filtered` out (because
+//!   their api version was incompatible, their configuration was invalid, or their factory failed
+//!   or panicked), and the `elapsed` time.
+//! - a `construct` span for each plugin, within `collect`, with the `key` of the plugin (for keyed
+//!   registries) and the `source` it was registered at. its factory runs within the span, so events
+//!   the factory emits are attributed to the plugin. when it closes, an event records the `outcome`
+//!   and the `elapsed` time. plugins which failed or panicked are reported at `WARN`.
+//! - a `select` event when plugins are selected by key, with how many were `registered` and
+//!   `selected`.
+//!
+//! without the feature, these are no-ops, and factories are called directly.
+
+use core::future::Future;
+
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+
+use crate::Source;
+
+#[cfg(feature = "tracing")]
+const TARGET: &str = "dyn_inventory";
+
+/// how the construction of a plugin ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// the plugin was instantiated
+    Loaded,
+    /// the factory returned an error
+    Failed,
+    /// the factory panicked
+    Panicked,
+    /// the configuration of the plugin was invalid, so its factory did not run
+    Misconfigured,
+}
+
+/// a collection of the plugins of one registry.
+pub struct Collection {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    start: Instant,
+    #[cfg(feature = "tracing")]
+    registered: usize,
+}
+
+/// the construction of one plugin.
+pub struct Construction {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    start: Instant,
+}
+
+impl Collection {
+    /// starts collecting the `registered` plugins of `registry`
+    #[allow(unused_variables)]
+    pub fn start(
+        registry: &'static str,
+        registered: usize,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(target: TARGET, "collect", registry),
+            #[cfg(feature = "tracing")]
+            start: Instant::now(),
+            #[cfg(feature = "tracing")]
+            registered,
+        }
+    }
+
+    /// starts constructing the plugin registered at `source`, under `key` for keyed registries
+    #[allow(unused_variables)]
+    pub fn construct(
+        &self,
+        key: Option<&'static str>,
+        source: &Source,
+    ) -> Construction {
+        Construction {
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                target: TARGET,
+                parent: &self.span,
+                "construct",
+                key,
+                source = %source,
+            ),
+            #[cfg(feature = "tracing")]
+            start: Instant::now(),
+        }
+    }
+
+    /// finishes collecting, with the number of plugins which were `collected`
+    #[allow(unused_variables)]
+    pub fn finish(
+        self,
+        collected: usize,
+    ) {
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            target: TARGET,
+            parent: &self.span,
+            registered = self.registered,
+            collected,
+            filtered = self.registered.saturating_sub(collected),
+            elapsed = ?self.start.elapsed(),
+            "collected plugins",
+        );
+    }
+}
+
+impl Construction {
+    /// calls the factory `f` of the plugin within its span
+    pub fn in_scope<T>(
+        &self,
+        f: impl FnOnce() -> T,
+    ) -> T {
+        #[cfg(feature = "tracing")]
+        {
+            self.span.in_scope(f)
+        }
+        #[cfg(not(feature = "tracing"))]
+        {
+            f()
+        }
+    }
+
+    /// polls the async factory `future` of the plugin within its span
+    pub fn instrument<F: Future>(
+        &self,
+        future: F,
+    ) -> impl Future<Output = F::Output> {
+        #[cfg(feature = "tracing")]
+        {
+            tracing::Instrument::instrument(future, self.span.clone())
+        }
+        #[cfg(not(feature = "tracing"))]
+        {
+            future
+        }
+    }
+
+    /// finishes constructing the plugin
+    #[allow(unused_variables)]
+    pub fn finish(
+        self,
+        outcome: Outcome,
+    ) {
+        #[cfg(feature = "tracing")]
+        {
+            let elapsed = self.start.elapsed();
+            match outcome {
+                Outcome::Loaded => {
+                    tracing::debug!(
+                        target: TARGET,
+                        parent: &self.span,
+                        ?outcome,
+                        ?elapsed,
+                        "constructed plugin",
+                    );
+                },
+                Outcome::Failed | Outcome::Panicked | Outcome::Misconfigured => {
+                    tracing::warn!(
+                        target: TARGET,
+                        parent: &self.span,
+                        ?outcome,
+                        ?elapsed,
+                        "plugin was not constructed",
+                    );
+                },
+            }
+        }
+    }
+}
+
+/// records that `selected` of the `registered` plugins of `registry` were selected by key
+#[allow(unused_variables)]
+pub fn select(
+    registry: &'static str,
+    registered: usize,
+    selected: usize,
+) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        target: TARGET,
+        registry,
+        registered,
+        selected,
+        "selected plugins",
+    );
+}
@@ -69,6 +69,7 @@
 //! [dependencies]
 //! inventory = "0.3"
 //! dyn-inventory = "0.2"
+//! dyn-inventory-runtime = "0.2"
 //! ```
 //!
 //! 2. define a trait that is dyn-compatible:
@@ -101,6 +102,7 @@
 //!  - a struct `GreeterPlugin` with the fields you declared, and a `Box<dyn Greeter>`
 //!  - an inventory registration type `inventory::collect!(GreeterPluginInit)`
 //!  - a collector `GreeterPluginCollector` that has `plugin` of type `Vec<GreeterPlugin>`
+//!  - implementations of the [`dyn-inventory-runtime`](https://docs.rs/dyn-inventory-runtime) traits, `Registry` for the collector and `Entry` for the struct
 //!
 //! 4. register a plugin somewhere in your code (could be another crate that depends on your trait crate):
 //!
@@ -153,15 +155,584 @@
 //!     };
 //!     // optional, comma-separated extra params
 //!     init_name = InitStructName,
+//!     key = field_name,
 //! );
 //! ```
 //!
 //! ## Extra Parameters
 //!
-//! two extra params are currently accepted:
+//! the following extra params are currently accepted:
 //!
 //! - `init_name = ident`
 //!   - sets the name of the generated initialization struct. by default it is the snake_case of `StructName` (for example, `GreeterPlugin` -> `greeter_plugin`).
+//! - `key = field`
+//!   - marks a `&'static str` metadata field as the unique name of each plugin. adds `StructName::key()`, `StructNameCollector::get(key)`, `StructNameCollector::spawn(key)`, and `StructNameRegistry`. see [Runtime registration](#runtime-registration).
+//! - `depends_on = field`
+//!   - marks a `&'static [&'static str]` metadata field as the keys of the plugins that must be instantiated first. requires `key`. see [Dependencies](#dependencies).
+//! - `backend = linkme`
+//!   - collects plugins with a `linkme` distributed slice instead of `inventory`. see [Backends](#backends).
+//! - `tags = field`
+//!   - marks a `&'static [&'static str]` metadata field as the tags of each plugin, and indexes the collector by tag. see [Tags](#tags).
+//! - `env_prefix = "PREFIX"`
+//!   - adds `StructNameCollector::from_env()`, which selects plugins from the `PREFIX_ENABLE` and `PREFIX_DISABLE` environment variables. requires `key`. see [Selecting plugins](#selecting-plugins).
+//! - `api_version = "2.3"`
+//!   - declares the version of the plugin api. `emit!` stamps it into each registration, and the collector sets aside registrations built against an incompatible version. see [Api versions](#api-versions).
+//! - `async_factory = true`
+//!   - registrations are instantiated by async factories. see [Async](#async).
+//! - `error = Type`
+//!   - factories return `Result<Handle, Type>`. registrations whose factory fails are reported in `StructNameCollector::failed`. see [Fallible factories](#fallible-factories).
+//! - `catch_unwind = true`
+//!   - catches panics in factories. registrations whose factory panics are reported in `StructNameCollector::panicked`. see [Fallible factories](#fallible-factories).
+//! - `dylib = true`
+//!   - adds `StructNameCollector::load(path)`, which loads plugins from shared libraries built with `export!`. requires the `dylib` feature of `dyn-inventory-runtime`. see [Shared libraries](#shared-libraries).
+//! - `downcast = true`
+//!   - adds `downcast_ref::<T>()` and `is::<T>()` to `StructName`, and `find_by_type::<T>()` to the collector. requires `TraitName: Any`. see [Downcasting](#downcasting).
+//! - `clap = field` / `help = field`
+//!   - names the `clap` argument and subcommand of each plugin by a `&'static str` metadata field, and describes them by another. the crate declaring the registry must depend on `clap`. see [Command line](#command-line).
+//! - `priority = field`
+//!   - marks an `Ord` metadata field, such as an `i32`, as the priority of each plugin. plugins are collected in ascending priority, but never before their dependencies. see [Lifecycle](#lifecycle).
+//! - `lifecycle = true`
+//!   - adds `StructNameCollector::start()`, which runs the `on_load` hook of each plugin and returns a `StructNameManaged` that runs their `on_unload` hooks in reverse when shut down or dropped. requires `TraitName: dyn_inventory_runtime::Lifecycle`. see [Lifecycle](#lifecycle).
+//! - `config = true`
+//!   - factories may take a configuration, deserialized from the section of a json or toml config under the key of their plugin. adds `StructNameCollector::from_config(config)` and `StructNameCollector::misconfigured`. requires `key`, and the `config` feature of `dyn-inventory-runtime`. see [Configuration](#configuration).
+//! - `serde = true`
+//!   - implements `serde::Serialize` for a view of the metadata of each plugin. the crate declaring the registry must depend on `serde`. see [Serde](#serde).
+//!
+//! ## Factories
+//!
+//! by default, `emit!` declares the handle as a unit struct. to build the handle instead, declare it yourself and pass a factory after the fields:
+//!
+//! ```rust,ignore
+//! pub struct Serial {
+//!     path: String,
+//! }
+//!
+//! impl Serial {
+//!     fn open() -> Self {
+//!         Self { path: "/dev/ttyS0".into() }
+//!     }
+//! }
+//!
+//! emit! {
+//!     Serial Device as DevicePlugin {
+//!         name = "serial",
+//!     };
+//!     factory = Serial::open,
+//! }
+//! ```
+//!
+//! the factory is called once per collection, and must not capture any state.
+//!
+//! each plugin keeps its factory, so the registry can hand out independent instances as well as the collected ones, for example one per connection. `plugin.new_instance()` runs the factory again, and with `key`, `DevicePluginCollector::spawn("serial")` does so for the plugin registered under a key:
+//!
+//! ```rust,ignore
+//! let collected = DevicePluginCollector::new();
+//! let serial = collected.spawn("serial").unwrap();
+//! assert_eq!(serial.handle.path(), "/dev/ttyS0");
+//! ```
+//!
+//! the instance has the same metadata as the collected plugin. with `error`, it is a `Result`, and with `async_factory`, both are async.
+//!
+//! ## Fallible factories
+//!
+//! with `error = Type`, factories return `Result<Handle, Type>`. a plugin that fails to start does not stop the others from loading: the collector keeps the plugins that loaded in `plugins`, and each failed registration alongside its error in `failed`:
+//!
+//! ```rust,ignore
+//! dyn_inventory!(
+//!     DevicePlugin<Handle: Device> {
+//!         pub name: &'static str,
+//!         handle: Handle,
+//!     };
+//!     error = std::io::Error,
+//! );
+//!
+//! impl Serial {
+//!     fn open() -> std::io::Result<Self> {
+//!         let file = std::fs::File::open("/dev/ttyS0")?;
+//!         Ok(Self { file })
+//!     }
+//! }
+//!
+//! emit! {
+//!     Serial Device as DevicePlugin {
+//!         name = "serial",
+//!     };
+//!     factory = Serial::open,
+//! }
+//!
+//! let collected = DevicePluginCollector::new();
+//! for (init, err) in &collected.failed {
+//!     eprintln!("{} failed to start: {err}", init.name);
+//! }
+//! ```
+//!
+//! handles declared by `emit!` without a factory always load. `StructNameInit::instantiate()` runs the factory of a single registration.
+//!
+//! a factory that panics still aborts the collection. with `catch_unwind = true`, each factory is called within `std::panic::catch_unwind`, and registrations whose factory panics are reported alongside the panic, a `dyn_inventory_runtime::Panic`, in `panicked`:
+//!
+//! ```rust,ignore
+//! let collected = DevicePluginCollector::new();
+//! for (init, panic) in &collected.panicked {
+//!     eprintln!("{} panicked: {:?}", init.name, panic.message());
+//! }
+//! ```
+//!
+//! the panic hook still runs before the panic is caught, and panics are only caught when the binary is built with `panic = "unwind"`.
+//!
+//! ## Async
+//!
+//! traits with async methods can be made dyn-compatible with `#[dyn_async]`, which rewrites each `async fn` of a trait or impl to return a boxed future, `dyn_inventory_runtime::BoxFuture`. the futures are `Send`, so the trait should declare `Send + Sync` as supertraits. without them, default methods are bounded by `where Self: Sync` for `&self` receivers and `where Self: Send` for `&mut self`, and can only be called through a `dyn Trait + Send + Sync`. use `#[dyn_async(?Send)]` for futures that are not `Send`.
+//!
+//! with `async_factory = true`, factories passed to `emit!` may be async, and the collector gains `new_async()` and `new_async_with(..)`:
+//!
+//! ```rust,ignore
+//! use dyn_inventory::{dyn_async, dyn_inventory, emit};
+//!
+//! #[dyn_async]
+//! pub trait Handler: Send + Sync {
+//!     async fn handle(&self, request: &str) -> String;
+//! }
+//!
+//! dyn_inventory!(
+//!     HandlerPlugin<Handle: Handler> {
+//!         name: &'static str,
+//!         handle: Handle,
+//!     };
+//!     async_factory = true,
+//! );
+//!
+//! pub struct Database { /* .. */ }
+//!
+//! async fn connect() -> Database { /* .. */ }
+//!
+//! emit! {
+//!     Database Handler as HandlerPlugin {
+//!         name = "database",
+//!     };
+//!     factory = connect,
+//! }
+//!
+//! #[dyn_async]
+//! impl Handler for Database {
+//!     async fn handle(&self, request: &str) -> String { /* .. */ }
+//! }
+//!
+//! let collected = HandlerPluginCollector::new_async().await;
+//! ```
+//!
+//! the blocking constructors, such as `new()`, drive the factories with `dyn_inventory_runtime::block_on`, which does not provide a reactor. factories that depend on a runtime, such as tokio, must be collected with `new_async()` within that runtime.
+//!
+//! ## Dependencies
+//!
+//! when `depends_on` is declared, plugins are instantiated and collected in dependency order:
+//!
+//! ```rust,ignore
+//! dyn_inventory!(
+//!     Service<Handle: Start> {
+//!         name: &'static str,
+//!         depends_on: &'static [&'static str],
+//!         handle: Handle,
+//!     };
+//!     key = name,
+//!     depends_on = depends_on,
+//! );
+//!
+//! emit! {
+//!     Handle Start as Service {
+//!         name = "http",
+//!         depends_on = &["metrics"],
+//!     }
+//! }
+//! ```
+//!
+//! `ServiceCollector::try_new()` and `try_new_with(..)` return a `ServiceDependencyError` naming the plugins involved when a key is registered twice, a dependency is not registered, or the dependencies form a cycle. `new()` and `new_with(..)` panic with the same error.
+//!
+//! ## Lifecycle
+//!
+//! plugins which hold resources, such as connections or worker threads, can acquire and release them at well defined points with `lifecycle = true`. the trait declares `dyn_inventory_runtime::Lifecycle` as a supertrait, whose hooks default to doing nothing:
+//!
+//! ```rust,ignore
+//! use dyn_inventory_runtime::{HookError, Lifecycle};
+//!
+//! pub trait Start: Lifecycle {}
+//!
+//! dyn_inventory!(
+//!     Service<Handle: Start> {
+//!         name: &'static str,
+//!         depends_on: &'static [&'static str],
+//!         priority: i32,
+//!         handle: Handle,
+//!     };
+//!     key = name,
+//!     depends_on = depends_on,
+//!     priority = priority,
+//!     lifecycle = true,
+//! );
+//!
+//! impl Lifecycle for Handle {
+//!     fn on_load(&mut self) -> Result<(), HookError> {
+//!         self.pool = Some(Pool::connect()?);
+//!         Ok(())
+//!     }
+//!
+//!     fn on_unload(&mut self) {
+//!         self.pool.take();
+//!     }
+//! }
+//!
+//! let services = ServiceCollector::try_new()?.start()?;
+//! ```
+//!
+//! `start()` runs `on_load` for each plugin in the order they were collected: after their dependencies, and otherwise in ascending `priority`, ties keeping registration order. the returned `ServiceManaged` hands out only started plugins, through `plugins()` and, with `key`, `get(key)`, so none can be used after it is shut down. `shutdown()` runs `on_unload` in reverse, as does dropping it. when a plugin fails to start, the plugins which started are stopped again, in reverse, and `start()` returns a `StartError` with the key and source of the plugin and the error of its hook.
+//!
+//! ## Configuration
+//!
+//! with `config = true`, each plugin is configured from its own section of one application config. this requires the `config` feature of the runtime crate, and `toml` to read `toml` tables as well as json:
+//!
+//! ```toml
+//! [dependencies]
+//! dyn-inventory-runtime = { version = "0.2", features = ["toml"] }
+//! ```
+//!
+//! a factory declares the type of its configuration by taking it as its argument. any `serde::Deserialize` type works, and plugins whose factory takes no argument are not configured:
+//!
+//! ```rust,ignore
+//! dyn_inventory!(
+//!     Service<Handle: Start> {
+//!         name: &'static str,
+//!         handle: Handle,
+//!     };
+//!     key = name,
+//!     config = true,
+//! );
+//!
+//! #[derive(serde::Deserialize)]
+//! pub struct HttpConfig {
+//!     port: u16,
+//!     #[serde(default)]
+//!     tls: Option<Tls>,
+//! }
+//!
+//! emit! {
+//!     Http Start as Service {
+//!         name = "http",
+//!     };
+//!     factory = |config: HttpConfig| Http::bind(config.port, config.tls),
+//! }
+//!
+//! let config: toml::Table = toml::from_str(&std::fs::read_to_string("app.toml")?)?;
+//! let services = ServiceCollector::from_config(config)?;
+//! ```
+//!
+//! `from_config` takes a `serde_json::Value` or a `toml::Table`, keyed by plugin name, and deserializes the section under each key into the configuration of that plugin before its factory runs. it fails with a `ConfigError` when the value is not a table of sections, or has a section no plugin is registered under, so a misspelled plugin name is not silently ignored. plugins without a section are configured from an empty table, which is also what `new()` and the other constructors do. registrations whose section does not match are not instantiated, and are reported in `ServiceCollector::misconfigured` with a `ConfigError::Invalid` naming the plugin and the field:
+//!
+//! ```text
+//! invalid configuration of plugin `http` at `tls.cert`: invalid type: integer `1`, expected a string
+//! invalid configuration of plugin `http`: missing field `port`
+//! ```
+//!
+//! further instances, from `new_instance()` and `spawn(key)`, are configured with the same section. `StructNameInit::configure(section)` configures a single registration, such as one built with `mock_plugin!`.
+//!
+//! ## Selecting plugins
+//!
+//! when `key` is declared, plugins can be switched on and off by key without a rebuild:
+//!
+//! ```rust,ignore
+//! // only `otlp` and `stdout`, minus `stdout`
+//! let collected = ExporterCollector::from_selection(&["otlp", "stdout"], &["stdout"])?;
+//! ```
+//!
+//! an empty allow list enables every plugin. naming a plugin that is not registered in either list returns `ExporterSelectionError::Unknown`, so a typo does not go unnoticed.
+//!
+//! with `env_prefix = "EXPORTER"`, `ExporterCollector::from_env()` reads the same lists from comma-separated environment variables, for example `EXPORTER_ENABLE=otlp,stdout` and `EXPORTER_DISABLE=stdout`.
+//!
+//! ## Tags
+//!
+//! with `tags = field`, plugins can be selected by capability. the collector builds an index of every tag when it is collected, so lookups don't scan every plugin:
+//!
+//! ```rust,ignore
+//! dyn_inventory!(
+//!     ExporterPlugin<Handle: Exporter> {
+//!         pub name: &'static str,
+//!         pub tags: &'static [&'static str],
+//!         handle: Handle,
+//!     };
+//!     tags = tags,
+//! );
+//!
+//! emit! {
+//!     OtlpGrpc Exporter as ExporterPlugin {
+//!         name = "otlp-grpc",
+//!         tags = &["otlp", "io/network/grpc"],
+//!     }
+//! }
+//!
+//! let collected = ExporterPluginCollector::new();
+//! let otlp: Vec<_> = collected.by_tag("otlp").collect();
+//! let grpc = collected.by_tags_all(&["otlp", "io/network/grpc"]);
+//! let either = collected.by_tags_any(&["otlp", "io/compression"]);
+//! ```
+//!
+//! tags may be hierarchical categories separated by `/`. a plugin tagged `io/network/grpc` is also found by `io/network` and `io`. `StructName::has_tag(tag)` matches the same way, and `StructNameCollector::tags()` lists every indexed tag and category. plugins are returned in registration order. `by_tags_all(&[])` returns every plugin, as every plugin holds all of no tags.
+//!
+//! as the index refers to plugins by position, `plugins` is private with `tags`. read them with `plugins()` and `into_plugins()`, and change them with `retain(keep)` and `extend(plugins)`, which rebuild the index:
+//!
+//! ```rust,ignore
+//! let mut collected = ExporterPluginCollector::new();
+//! collected.retain(|plugin| !plugin.has_tag("io/network"));
+//! assert!(collected.by_tag("io/network").next().is_none());
+//! ```
+//!
+//! ## Api versions
+//!
+//! with `api_version = "2.3"`, every registration made with `emit!` records the api version of the trait crate it was compiled against. the collector compares it with the registry's version using cargo's caret rules: a plugin built against `2.1` is accepted by a registry at `2.3`, while one built against `2.4` or `1.9` is not.
+//!
+//! incompatible registrations are not instantiated. they are reported in the collector's `incompatible` field, alongside the reason:
+//!
+//! ```rust,ignore
+//! let collected = HandlerPluginCollector::new();
+//! for (init, mismatch) in &collected.incompatible {
+//!     eprintln!("skipped {} from {}: {mismatch}", init.name, init.source());
+//!     // >> skipped legacy from `legacy_plugin` (legacy-plugin 0.1.0, src/lib.rs:4): built against api version 1.4, which is not compatible with 2.3
+//! }
+//! ```
+//!
+//! plugins loaded from [shared libraries](#shared-libraries) are checked the same way, against the api version the library's copy of the registry declares.
+//!
+//! ## Downcasting
+//!
+//! with `downcast = true`, a host that knows about a particular plugin can reach its inherent API. the trait must declare `Any` as a supertrait, and the handle must be visible to the host, which `emit!` allows by prefixing the handle name with a visibility:
+//!
+//! ```rust,ignore
+//! pub trait Codec: std::any::Any {
+//!     fn name(&self) -> &'static str;
+//! }
+//!
+//! dyn_inventory!(
+//!     CodecPlugin<Handle: Codec> {
+//!         handle: Handle,
+//!     };
+//!     downcast = true,
+//! );
+//!
+//! pub mod gzip {
+//!     dyn_inventory::emit! {
+//!         pub Handle Codec as CodecPlugin {}
+//!     }
+//!
+//!     impl Handle {
+//!         pub fn level(&self) -> u32 { 6 }
+//!     }
+//! }
+//!
+//! let collected = CodecPluginCollector::new();
+//! let gzip = collected.find_by_type::<gzip::Handle>().unwrap();
+//! assert_eq!(gzip.downcast_ref::<gzip::Handle>().unwrap().level(), 6);
+//! ```
+//!
+//! ## Shared libraries
+//!
+//! with `dylib = true`, plugins can also be shipped as shared libraries and loaded at runtime. this requires the `dylib` feature of the runtime crate:
+//!
+//! ```toml
+//! [dependencies]
+//! dyn-inventory-runtime = { version = "0.2", features = ["dylib"] }
+//! ```
+//!
+//! the registry is declared in a library crate which both the host and the plugins depend on. the handle field must be `pub`, so that plugins in other crates can register with `emit!`:
+//!
+//! ```rust,ignore
+//! dyn_inventory!(
+//!     TransformPlugin<Handle: Transform> {
+//!         pub name: &'static str,
+//!         pub handle: Handle,
+//!     };
+//!     dylib = true,
+//! );
+//! ```
+//!
+//! a plugin crate is built as a `cdylib`, registers its plugins with `emit!` as usual, and exports them with `export!`:
+//!
+//! ```rust,ignore
+//! use dyn_inventory::{emit, export};
+//! use transforms::{Transform, TransformPluginInit};
+//!
+//! export!(TransformPlugin);
+//!
+//! emit! {
+//!     Shout Transform as TransformPlugin {
+//!         name = "shout",
+//!     }
+//! }
+//! ```
+//!
+//! the host loads each library into a collector, alongside the plugins which are linked in:
+//!
+//! ```rust,ignore
+//! let mut collected = TransformPluginCollector::new();
+//! unsafe { collected.load("plugins/libshout.so")? };
+//! ```
+//!
+//! a `Box<dyn Trait>` has no stable abi, so the library exports a versioned, `#[repr(C)]` table which the host checks before reading any registrations: the library must be built against the same registry, with registrations of the same size, by the same version of rustc. libraries which don't match are rejected with a `LoadError`. with `api_version`, the table also records the api version the library was built against, and plugins of a library built against an incompatible api version are reported in `incompatible` rather than instantiated. the library exports the plugins of every crate bundled into it, except those of the registry crate itself, which the host already links.
+//!
+//! the metadata of a loaded plugin, such as its `&'static str` key, its source, and the code of its handle, point into the library. they can be copied out of the plugin, its registration, or a `StructNameDependencyError`, so once its registrations are read, a library is never unloaded, and they remain valid for the rest of the process. with `depends_on`, the plugins of a library are ordered amongst themselves.
+//!
+//! `load` is `unsafe`, as the table can only be checked so far. the caller must ensure that:
+//!
+//! - the initializers of the library, which run when it is opened, are sound
+//! - the library exports the registry's table with `export!`, and nothing else under its symbol
+//! - the library is built against a version of the registry crate which declares the registry and its trait as the host's does. its name and module, and the size of a registration, are checked, but other changes are not detected. with `api_version`, the trait of plugins built against an incompatible api version may differ, as they are never instantiated
+//! - the library and the host use the same global allocator. handles, and any values they return, are allocated by the library and dropped by the host, so a `#[global_allocator]` declared in only one of them is undefined behaviour
+//! - the file is not modified in place while it is loaded. replace it with a new file instead
+//!
+//! ### Reloading
+//!
+//! `StructNameReloader` keeps the plugins of every shared library in a directory up to date for a long-running host. each reload builds a new collection from the linked plugins and the libraries currently in the directory, and counts a new generation:
+//!
+//! ```rust,ignore
+//! let reloader = unsafe { TransformPluginReloader::new("plugins")? };
+//!
+//! loop {
+//!     if let Some(generation) = unsafe { reloader.reload_if_changed()? } {
+//!         println!("loaded generation {generation}");
+//!     }
+//!
+//!     let collected = reloader.current();
+//!     // ...
+//! }
+//! ```
+//!
+//! `current()` returns the latest collection behind an `Arc`. collections of previous generations, and the plugins taken from them, remain valid while they are held, while new calls to `current()` see the new plugins. libraries are opened from a private copy, so a library which changed on disk is loaded anew even while its previous version is in use. as libraries are never unloaded, each reload keeps the previous copies in memory, so reload when the libraries changed rather than on a timer. the growth is bounded: a reloader loads at most `StructNameReloader::DEFAULT_LIMIT` (256) copies of libraries over its lifetime, or the limit given to `StructNameReloader::with_limit(dir, limit)`, and `loaded()` counts the copies so far. a reload which would exceed the limit fails with `LoadError::Limit`, keeping the current collection, and only restarting the process frees the copies.
+//!
+//! a reload is all or nothing: when any library fails to load, for example because it was built against a different registry or compiler, `reload()` returns the error and the current collection is kept.
+//!
+//! ## Serde
+//!
+//! with `serde = true`, `StructNameMetadata<'_>` is generated: a view of every metadata field that skips the handle. `StructName`, `StructNameInit` and `StructNameCollector` implement `serde::Serialize` through it, so a plugin listing can be serialized directly. `#[serde(..)]` attributes on the declared fields are applied to the view:
+//!
+//! ```toml
+//! [dependencies]
+//! serde = { version = "1", features = ["derive"] }
+//! ```
+//!
+//! ```rust,ignore
+//! dyn_inventory!(
+//!     Endpoint<Handle: Serve> {
+//!         #[serde(rename = "id")]
+//!         name: &'static str,
+//!         #[serde(skip)]
+//!         secret: &'static str,
+//!         handle: Handle,
+//!     };
+//!     serde = true,
+//! );
+//!
+//! let listing = serde_json::to_string(&EndpointCollector::new())?;
+//! // >> [{"id":"health"}]
+//! ```
+//!
+//! only registries which set `serde = true` refer to `serde`, so crates declaring other registries don't need to depend on it. `#[serde(..)]` attributes on the fields of a registry without it fail to compile.
+//!
+//! ## Command line
+//!
+//! `clap = field` turns each plugin into a [`clap`](https://docs.rs/clap) option or subcommand named by that field, so plugin crates add to a cli without the binary knowing about them. `help = field` is shown as the description of each:
+//!
+//! ```toml
+//! [dependencies]
+//! clap = "4"
+//! ```
+//!
+//! ```rust,ignore
+//! dyn_inventory!(
+//!     Flag<Handle: Greeter> {
+//!         flag_name: &'static str,
+//!         desc: &'static str,
+//!         handle: Handle,
+//!     };
+//!     clap = flag_name,
+//!     help = desc,
+//! );
+//!
+//! let flags = FlagCollector::new();
+//! let matches = flags
+//!     .augment_args(clap::Command::new("greeter"))
+//!     .get_matches();
+//!
+//! for (flag, value) in flags.matched_args(&matches) {
+//!     flag.handle.handle(value, &mut stdout())?;
+//! }
+//! ```
+//!
+//! `StructName::arg()` is an option taking a value, `--flag_name <VALUE>`, and `StructName::subcommand()` is a subcommand. `StructNameCollector::augment_args(command)` and `augment_subcommands(command)` add every plugin to a `clap::Command`. after parsing, `matched_args(&matches)` yields each plugin whose option was passed with its value, and `matched_subcommand(&matches)` the plugin whose subcommand was passed with the matches of the subcommand. the arguments are plain `clap::Arg`s and `clap::Command`s, so they can be adjusted before they are added. only registries which set `clap` refer to `clap`, so crates declaring other registries don't need to depend on it.
+//!
+//! without `clap`, the companion crate [`dyn-inventory-cli`](dyn-inventory-cli) parses a command line whose flags are the plugins of a registry. each plugin carries a `Spec` naming its long and short aliases, whether it is a switch or takes a value, whether it may be repeated, and its help:
+//!
+//! ```rust,ignore
+//! use dyn_inventory_cli::{Cli, Parsed, Spec};
+//!
+//! dyn_inventory!(
+//!     Flag<Handle: Greeter> {
+//!         spec: Spec,
+//!         handle: Handle,
+//!     };
+//! );
+//!
+//! impl dyn_inventory_cli::Flag for Flag {
+//!     fn spec(&self) -> &Spec {
+//!         &self.spec
+//!     }
+//! }
+//!
+//! emit! {
+//!     Hello Greeter as Flag {
+//!         spec = Spec::value("name").short('n').help("greets someone").repeated(),
+//!     }
+//! }
+//!
+//! match Cli::from_registry::<FlagCollector>("greeter").parse(std::env::args())? {
+//!     Parsed::Help(help) => print!("{help}"),
+//!     Parsed::Matches(matches) => {
+//!         for occurrence in matches.flags {
+//!             occurrence.flag.handle.handle(&occurrence.value, &mut stdout())?;
+//!         }
+//!     },
+//! }
+//! ```
+//!
+//! values are parsed before any plugin is dispatched to: as a `String` by default, or as any `FromStr` type set per registration with `Spec::of`, such as `Spec::value("port").of::<u16>()` or `.of::<PathBuf>()`. plugins of one registry can take different types, so the trait receives a type-erased `Value`, and each plugin gets its parsed value back with `value.get::<u16>()`.
+//!
+//! to declare the type only once, plugins implement `dyn_inventory_cli::Handler`, whose `Value` both parses the value and is what the plugin is passed. the registry trait is a subtrait of `DynHandler`, which erases the type, and `Flag::parse` parses with the handle:
+//!
+//! ```rust,ignore
+//! use dyn_inventory_cli::{DynHandler, Handler, Value};
+//!
+//! pub trait Greeter: for<'w> DynHandler<dyn Write + 'w, std::io::Result<()>> {}
+//!
+//! impl<H: for<'w> DynHandler<dyn Write + 'w, std::io::Result<()>>> Greeter for H {}
+//!
+//! impl dyn_inventory_cli::Flag for Flag {
+//!     fn spec(&self) -> &Spec {
+//!         &self.spec
+//!     }
+//!
+//!     fn parse(&self, raw: &str) -> Result<Value, String> {
+//!         self.handle.parse(raw)
+//!     }
+//! }
+//!
+//! impl<'w> Handler<dyn Write + 'w> for Port {
+//!     type Value = u16;
+//!     type Output = std::io::Result<()>;
+//!
+//!     fn handle(&self, port: &u16, w: &mut (dyn Write + 'w)) -> std::io::Result<()> {
+//!         writeln!(w, "listening on {port}")
+//!     }
+//! }
+//! ```
+//!
+//! a value starting with `-`, such as `--offset -5`, is taken as the value unless it starts with a declared short flag, and is reported as invalid when it doesn't parse. long flags are never taken as values.
+//!
+//! unknown flags, missing values, values which don't parse, repeated flags which aren't `repeated()` and unexpected positional arguments are reported as a `ParseError`, such as ``invalid value `x` for `--port`: invalid digit found in string``, and `--help` renders help from the specs. see [`examples/cli-flags`](examples/cli-flags) for a complete cli.
 //!
 //! ## Advanced: customizing collection
 //!
@@ -169,46 +740,225 @@
 //!
 //! - `new()` -> builds the collection without modification
 //! - `new_with(|item: &mut StructName| {...})` -> allows you to mutate the raw entries after they are instantiated into `Box<dyn TraitName>`
+//! - `from_inits(inits)` / `from_inits_with(inits, |item| {...})` -> builds the collection from the given registrations instead of the registered ones. see [Testing](#testing).
+//!
+//! ## Runtime registration
+//!
+//! plugins which can't be submitted at link time, such as those assembled from configuration at startup, can be registered at runtime. with `key`, a `StructNameRegistry` is generated, which starts from the link-time registrations:
+//!
+//! ```rust,ignore
+//! let registry = GreeterPluginRegistry::new();
+//!
+//! registry.register(mock_plugin! {
+//!     GreeterPlugin {
+//!         name = "configured",
+//!     };
+//!     handle = Configured,
+//! });
+//! registry.unregister("legacy");
+//!
+//! let collected = registry.collect();
+//! ```
+//!
+//! `register` replaces any registration under the same key, and `unregister` removes registrations whether they were submitted at link time or at runtime. the registrations are held behind an `RwLock`, so both only need `&self`, and the registry can be shared between threads. `collect()` builds a collector from a snapshot of the current registrations; collectors built earlier are unaffected.
+//!
+//! unlike registrations submitted at link time, those built at runtime may capture state. the `handle` expression of `mock_plugin!` is evaluated each time the plugin is instantiated, within a closure shared by every copy of the registration, so it clones what it captures:
+//!
+//! ```rust,ignore
+//! let greeting = settings.greeting.clone();
+//! registry.register(mock_plugin! {
+//!     GreeterPlugin {
+//!         name = "configured",
+//!     };
+//!     handle = Configured(greeting.clone()),
+//! });
+//! ```
+//!
+//! ## Testing
+//!
+//! every test binary sees every plugin linked into it. to test host logic against known plugins, build the registrations with `mock_plugin!`, which does not submit them, and collect them with `from_inits`:
+//!
+//! ```rust,ignore
+//! use dyn_inventory::mock_plugin;
+//!
+//! struct Double;
+//!
+//! impl Greeter for Double {
+//!     fn greet(&self) -> String {
+//!         "double".to_string()
+//!     }
+//! }
+//!
+//! let collected = GreeterPluginCollector::from_inits([
+//!     mock_plugin! {
+//!         GreeterPlugin {
+//!             name = "double",
+//!         };
+//!         handle = Double,
+//!     },
+//! ]);
+//! ```
+//!
+//! `mock_plugin!` takes either a `handle = expr`, or a `factory = closure` as in `emit!`. unlike in `emit!`, both may capture state: the handle expression is evaluated each time the plugin is instantiated, and the factory is cloned for each run, so it must be `Clone`, as closures capturing only `Clone` values are. either must be `Send` and `Sync`, as registrations are shared between threads. `from_inits` runs the same stages as `new()`, so with `depends_on` it returns a `Result`.
+//!
+//! ## Registration source
+//!
+//! `emit!` records where each plugin was registered: its module path, file, line, and the name and version of the registering crate. these are exposed through `source()` on both `StructName` and `StructNameInit`, and are included in duplicate key errors:
+//!
+//! ```rust,ignore
+//! for plugin in &GreeterPluginCollector::new().plugins {
+//!     println!("{} registered by {}", plugin.name, plugin.source());
+//!     // >> hello registered by `my_crate::greeters` (my-crate 0.1.0, src/greeters.rs:12)
+//! }
+//! ```
+//!
+//! ## Tracing
+//!
+//! with the `tracing` feature of the runtime crate, every collector records collection with [`tracing`](https://docs.rs/tracing), so slow or failing plugins show up in an existing subscriber:
+//!
+//! ```toml
+//! [dependencies]
+//! dyn-inventory-runtime = { version = "0.2", features = ["tracing"] }
+//! ```
+//!
+//! each collection runs in a `collect` span with the `registry` name, and each plugin is constructed in a `construct` span within it, with the plugin's `key` (for keyed registries) and `source`. factories run inside their span, so whatever they log is attributed to their plugin:
+//!
+//! ```text
+//! DEBUG collect{registry="GreeterPlugin"}:construct{key="hello" source=`my_crate::greeters` (my-crate 0.1.0, src/greeters.rs:12)}: dyn_inventory: constructed plugin outcome=Loaded elapsed=1.2ms
+//!  WARN collect{registry="GreeterPlugin"}:construct{key="remote" source=..}: dyn_inventory: plugin was not constructed outcome=Failed elapsed=30.4ms
+//!  INFO collect{registry="GreeterPlugin"}: dyn_inventory: collected plugins registered=2 collected=1 filtered=1 elapsed=31.7ms
+//! ```
+//!
+//! plugins whose factory failed or panicked, or whose api version is incompatible, are counted as `filtered`. selecting plugins by key logs how many of the registered plugins were selected. events use the target `dyn_inventory`, to filter them separately from the application's. the feature requires `std`, and without it the instrumentation compiles away.
+//!
+//! ## Generic tooling
+//!
+//! every generated collector implements `dyn_inventory_runtime::Registry`, and every generated struct implements `dyn_inventory_runtime::Entry`. tooling can be written once for any registry:
+//!
+//! ```rust,ignore
+//! use dyn_inventory_runtime::{Entry, Registry};
+//!
+//! fn greet_all<R: Registry<Handle = dyn Greeter>>() {
+//!     for plugin in R::collect() {
+//!         println!("{}", plugin.handle().greet());
+//!     }
+//! }
+//!
+//! greet_all::<GreeterPluginCollector>();
+//! ```
+//!
+//! `Registry::collect()` panics where `new()` does, when the dependencies of a registry declared with `depends_on` cannot be ordered. `Registry::try_collect()` returns the `StructNameDependencyError` instead, and never fails for other registries, whose `Registry::Error` is `Infallible`.
+//!
+//! ## Building macros on dyn-inventory
+//!
+//! the macros of `dyn-inventory` are a thin shim over [`dyn-inventory-core`](dyn-inventory-core), a normal library crate which parses each macro into a public model, such as `dyn_inventory_core::Args` for a registry declaration, and expands it with `quote::ToTokens`. macros of your own can declare a registry, or inspect one, without going through `dyn_inventory!`:
+//!
+//! ```rust,ignore
+//! #[proc_macro]
+//! pub fn service_registry(tok: proc_macro::TokenStream) -> proc_macro::TokenStream {
+//!     let name = syn::parse_macro_input!(tok as syn::Ident);
+//!     let args: dyn_inventory_core::Args = syn::parse_quote! {
+//!         #name<Handle: Service> {
+//!             pub name: &'static str,
+//!             handle: Handle
+//!         };
+//!         key = name,
+//!     };
+//!     quote::quote! { #args }.into()
+//! }
+//! ```
+//!
+//! the expansions of `dyn-inventory-core` are tested as pretty-printed snapshots in `dyn-inventory-core/tests/snapshots`. run its tests with `SNAPSHOTS=overwrite` to accept a changed expansion.
+//!
+//! ## Backends
+//!
+//! by default, `emit!` submits registrations with `inventory`, which collects them with constructors that run before `main`. with `backend = linkme`, registrations are placed in a [`linkme`](https://docs.rs/linkme) distributed slice instead, assembled by the linker with no code running at startup:
+//!
+//! ```rust,ignore
+//! dyn_inventory!(
+//!     GreeterPlugin<Handle: Greeter> {
+//!         name: &'static str,
+//!         handle: Handle,
+//!     };
+//!     backend = linkme,
+//! );
+//!
+//! emit! {
+//!     Hello Greeter as GreeterPlugin {
+//!         name = "hello",
+//!     };
+//!     backend = linkme,
+//! }
+//! ```
+//!
+//! `emit!` can't see the options of the registry, so it takes the same `backend`, and fails to compile when the two differ. the crates declaring the registry and emitting its plugins must depend on `linkme`:
+//!
+//! ```toml
+//! [dependencies]
+//! linkme = "0.3"
+//! ```
+//!
+//! the slice is a static named after the registration type, so importing `StructNameInit` for `emit!` imports the slice too, and `StructNameInit::iter()` walks the registrations with either backend. the collector api is the same for both. the backend is chosen by each registry, so registries with different backends can be linked into one binary. linkme names the slice's link section after the registration type, so two registries with the same name can't both use `linkme` in one binary.
+//!
+//! ## `no_std`
+//!
+//! generated code only uses fully qualified paths, through `core` and `dyn_inventory_runtime`, so it works in `no_std` crates which have `alloc`, and isn't affected by items which shadow the prelude. disable the default `std` feature of the runtime, and use the `linkme` backend, which needs neither `std` nor constructors run before `main`:
+//!
+//! ```toml
+//! [dependencies]
+//! dyn-inventory = "0.2"
+//! dyn-inventory-runtime = { version = "0.2", default-features = false }
+//! linkme = "0.3"
+//! ```
+//!
+//! ```rust,ignore
+//! #![no_std]
+//!
+//! dyn_inventory!(
+//!     Checksum<Handle: Digest> {
+//!         name: &'static str,
+//!         handle: Handle,
+//!     };
+//!     key = name,
+//!     backend = linkme,
+//! );
+//! ```
+//!
+//! `catch_unwind`, `env_prefix` and `dylib` require `std`. without it, the blocking constructors of async registries poll the factories until they complete, and `StructNameRegistry` guards its registrations with a spin lock. see [`examples/no-std`](examples/no-std) for a complete registry.
 //!
 //! ## Constraints
 //!
 //! - your trait must be object-safe (dyn-compatible)
-//! - the `inventory` crate must be linked into the final binary; ensure your plugin crates depend on `inventory` and your main binary pulls in the crates that perform registrations
+//! - the `inventory` crate (or `linkme`, see [Backends](#backends)) must be linked into the final binary; ensure your plugin crates depend on `inventory` and your main binary pulls in the crates that perform registrations
 //! - plugins must not carry state. instead, pass state as trait function parameters.
 /* END OF README CONTENTS */
 
-mod args;
-mod declare;
-
 use proc_macro::TokenStream;
-use proc_macro2::Span;
-
-use syn::Ident;
 
-use crate::{args::Args, declare::EmitArgs};
-
-pub(crate) fn init(strct: &Ident) -> Ident {
-    Ident::new(&format!("{strct}Init"), Span::call_site())
+#[proc_macro]
+pub fn dyn_inventory(tok: TokenStream) -> TokenStream {
+    dyn_inventory_core::dyn_inventory(tok.into()).into()
 }
 
 #[proc_macro]
-pub fn dyn_inventory(tok: TokenStream) -> TokenStream {
-    let args: Args = match syn::parse(tok) {
-        Ok(parse) => parse,
-        Err(e) => {
-            return e.into_compile_error().into();
-        },
-    };
+pub fn emit(tok: TokenStream) -> TokenStream {
+    dyn_inventory_core::emit(tok.into()).into()
+}
 
-    quote::quote! { #args }.into()
+#[proc_macro]
+pub fn mock_plugin(tok: TokenStream) -> TokenStream {
+    dyn_inventory_core::mock_plugin(tok.into()).into()
 }
 
 #[proc_macro]
-pub fn emit(tok: TokenStream) -> TokenStream {
-    let emit: EmitArgs = match syn::parse2(tok.into()) {
-        Ok(emit) => emit,
-        Err(e) => return e.into_compile_error().into(),
-    };
+pub fn export(tok: TokenStream) -> TokenStream {
+    dyn_inventory_core::export(tok.into()).into()
+}
 
-    quote::quote! { #emit }.into()
+#[proc_macro_attribute]
+pub fn dyn_async(
+    attr: TokenStream,
+    item: TokenStream,
+) -> TokenStream {
+    dyn_inventory_core::dyn_async(attr.into(), item.into()).into()
 }
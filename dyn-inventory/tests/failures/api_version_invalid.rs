@@ -0,0 +1,20 @@
+// api_version must be a semver version
+//
+//
+//
+//
+//
+//
+//
+//
+//
+use dyn_inventory::dyn_inventory;
+
+dyn_inventory!(
+    Plugin<T: PluginApi> {
+        t: T,
+    };
+    api_version = "two",
+);
+
+fn main() {}
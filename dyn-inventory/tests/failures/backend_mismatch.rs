@@ -0,0 +1,35 @@
+// plugins must be submitted with the backend of the registry
+//
+//
+//
+//
+//
+//
+//
+//
+//
+use dyn_inventory::dyn_inventory;
+
+pub trait Greeter {
+    fn greet(&self) -> String;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Greeter> {
+        handle: Handle
+    };
+    backend = linkme,
+}
+
+dyn_inventory::emit! {
+    Hello Greeter as Plugin {};
+    backend = inventory,
+}
+
+impl Greeter for Hello {
+    fn greet(&self) -> String {
+        "hello".into()
+    }
+}
+
+fn main() {}
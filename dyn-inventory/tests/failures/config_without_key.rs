@@ -0,0 +1,12 @@
+// each plugin is configured by the section under its key, so a registry without one cannot be configured
+use dyn_inventory::dyn_inventory;
+
+dyn_inventory!(
+    Plugin<T: PluginApi> {
+        name: &'static str,
+        t: T,
+    };
+    config = true,
+);
+
+fn main() {}
@@ -0,0 +1,22 @@
+// depends_on cannot be resolved without a key field
+//
+//
+//
+//
+//
+//
+//
+//
+//
+use dyn_inventory::dyn_inventory;
+
+dyn_inventory!(
+    Plugin<T: PluginApi> {
+        name: &'static str,
+        deps: &'static [&'static str],
+        t: T,
+    };
+    depends_on = deps,
+);
+
+fn main() {}
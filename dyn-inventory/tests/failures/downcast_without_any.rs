@@ -0,0 +1,24 @@
+// downcasting requires the trait to have `Any` as a supertrait
+//
+//
+//
+//
+//
+//
+//
+//
+//
+use dyn_inventory::dyn_inventory;
+
+pub trait Codec {
+    fn name(&self) -> &'static str;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Codec> {
+        handle: Handle
+    };
+    downcast = true,
+}
+
+fn main() {}
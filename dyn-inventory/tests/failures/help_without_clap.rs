@@ -0,0 +1,13 @@
+// help describes the clap argument, so it cannot be declared without one
+use dyn_inventory::dyn_inventory;
+
+dyn_inventory!(
+    Plugin<T: PluginApi> {
+        name: &'static str,
+        desc: &'static str,
+        t: T,
+    };
+    help = desc,
+);
+
+fn main() {}
@@ -0,0 +1,21 @@
+// key must name one of the declared metadata fields
+//
+//
+//
+//
+//
+//
+//
+//
+//
+use dyn_inventory::dyn_inventory;
+
+dyn_inventory!(
+    Plugin<T: PluginApi> {
+        name: &'static str,
+        t: T,
+    };
+    key = id,
+);
+
+fn main() {}
@@ -0,0 +1,21 @@
+// key must name a field declared as `&'static str`
+//
+//
+//
+//
+//
+//
+//
+//
+//
+use dyn_inventory::dyn_inventory;
+
+dyn_inventory!(
+    Plugin<T: PluginApi> {
+        id: u32,
+        t: T,
+    };
+    key = id,
+);
+
+fn main() {}
@@ -0,0 +1,19 @@
+// exactly one field must have the type of the generic parameter
+//
+//
+//
+//
+//
+//
+//
+//
+//
+use dyn_inventory::dyn_inventory;
+
+dyn_inventory!(
+    NoHandleRegistry<T: PluginApi> {
+        name: &'static str,
+    };
+);
+
+fn main() {}
@@ -0,0 +1,14 @@
+// serde attributes only apply to the serializable metadata view, which is opt-in
+use dyn_inventory::dyn_inventory;
+
+pub trait Endpoint {}
+
+dyn_inventory! {
+    Plugin<Handle: Endpoint> {
+        #[serde(rename = "id")]
+        pub name: &'static str,
+        handle: Handle
+    };
+}
+
+fn main() {}
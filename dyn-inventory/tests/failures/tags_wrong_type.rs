@@ -0,0 +1,21 @@
+// tags must name a field declared as `&'static [&'static str]`
+//
+//
+//
+//
+//
+//
+//
+//
+//
+use dyn_inventory::dyn_inventory;
+
+dyn_inventory!(
+    Plugin<T: PluginApi> {
+        tags: &'static str,
+        t: T,
+    };
+    tags = tags,
+);
+
+fn main() {}
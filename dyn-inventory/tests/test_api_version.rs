@@ -0,0 +1,84 @@
+#![allow(unused)]
+use dyn_inventory::dyn_inventory;
+use dyn_inventory_runtime::ApiMismatch;
+
+pub trait Handler {
+    fn handle(&self) -> &'static str;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Handler> {
+        pub name: &'static str,
+        handle: Handle
+    };
+    api_version = "2.3",
+}
+
+mod current {
+    use crate::{Handler, PluginInit};
+
+    dyn_inventory::emit! {
+        Handle Handler as Plugin {
+            name = "current",
+        }
+    }
+
+    impl Handler for Handle {
+        fn handle(&self) -> &'static str {
+            "current"
+        }
+    }
+}
+
+mod outdated {
+    use crate::{Handler, PluginInit};
+
+    struct Handle;
+
+    impl Handler for Handle {
+        fn handle(&self) -> &'static str {
+            "outdated"
+        }
+    }
+
+    // what `emit!` expands to in a plugin built against api version 1.4 of the trait crate
+    const OUTDATED: PluginInit = PluginInit {
+        name: "outdated",
+        __get: dyn_inventory_runtime::Constructor::new(|| Box::new(Handle)),
+        __source: dyn_inventory_runtime::source!(),
+        __api_version: Some("1.4"),
+    };
+
+    inventory::submit! { OUTDATED }
+}
+
+#[test]
+fn stamps_registrations() {
+    assert_eq!(PluginInit::API_VERSION, Some("2.3"));
+    let current = PluginInit::iter()
+        .find(|init| init.name == "current")
+        .unwrap();
+    assert_eq!(current.api_version(), Some("2.3"));
+}
+
+#[test]
+fn sets_aside_incompatible_plugins() {
+    let collected = PluginCollector::new();
+    assert_eq!(collected.plugins.len(), 1);
+    assert_eq!(collected.plugins[0].handle.handle(), "current");
+
+    assert_eq!(collected.incompatible.len(), 1);
+    let (init, mismatch) = &collected.incompatible[0];
+    assert_eq!(init.name, "outdated");
+    assert_eq!(
+        *mismatch,
+        ApiMismatch {
+            expected: "2.3",
+            found: Some("1.4")
+        }
+    );
+    assert_eq!(
+        mismatch.to_string(),
+        "built against api version 1.4, which is not compatible with 2.3"
+    );
+}
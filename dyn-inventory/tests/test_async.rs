@@ -0,0 +1,149 @@
+#![allow(unused)]
+use dyn_inventory::{dyn_async, dyn_inventory};
+use dyn_inventory_runtime::block_on;
+
+#[dyn_async]
+pub trait Handler: Send + Sync {
+    async fn handle(
+        &self,
+        request: &str,
+    ) -> String;
+
+    async fn describe(&self) -> String {
+        format!("handler for {}", self.route())
+    }
+
+    fn route(&self) -> &'static str;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Handler> {
+        pub name: &'static str,
+        handle: Handle
+    };
+    key = name,
+    async_factory = true,
+}
+
+mod echo {
+    use dyn_inventory::dyn_async;
+
+    use crate::{Handler, PluginInit};
+
+    dyn_inventory::emit! {
+        Handle Handler as Plugin {
+            name = "echo",
+        }
+    }
+
+    #[dyn_async]
+    impl Handler for Handle {
+        async fn handle(
+            &self,
+            request: &str,
+        ) -> String {
+            request.to_string()
+        }
+
+        fn route(&self) -> &'static str {
+            "/echo"
+        }
+    }
+}
+
+mod greeting {
+    use dyn_inventory::dyn_async;
+
+    use crate::{Handler, PluginInit};
+
+    pub struct Handle {
+        greeting: String,
+    }
+
+    async fn connect() -> Handle {
+        Handle {
+            greeting: "hello".into(),
+        }
+    }
+
+    dyn_inventory::emit! {
+        Handle Handler as Plugin {
+            name = "greeting",
+        };
+        factory = connect,
+    }
+
+    #[dyn_async]
+    impl Handler for Handle {
+        async fn handle(
+            &self,
+            request: &str,
+        ) -> String {
+            format!("{}, {request}", self.greeting)
+        }
+
+        fn route(&self) -> &'static str {
+            "/greeting"
+        }
+    }
+}
+
+fn assert_send<T: Send>(_: &T) {}
+
+#[test]
+fn new_async() {
+    let collected = block_on(PluginCollector::new_async());
+    assert_eq!(collected.plugins.len(), 2);
+
+    let echo = collected.get("echo").unwrap();
+    let response = echo.handle.handle("ping");
+    assert_send(&response);
+    assert_eq!(block_on(response), "ping");
+    assert_eq!(block_on(echo.handle.describe()), "handler for /echo");
+
+    let greeting = collected.get("greeting").unwrap();
+    assert_eq!(block_on(greeting.handle.handle("world")), "hello, world");
+}
+
+#[test]
+fn blocking_new() {
+    let collected = PluginCollector::new();
+    let greeting = collected.get("greeting").unwrap();
+    assert_eq!(block_on(greeting.handle.handle("there")), "hello, there");
+}
+
+/// a trait without supertraits, whose default methods bound `Self` themselves
+#[dyn_async]
+pub trait Counter {
+    async fn count(&self) -> usize;
+
+    async fn doubled(&self) -> usize {
+        self.count().await * 2
+    }
+
+    async fn reset(&mut self) -> usize {
+        self.count().await
+    }
+}
+
+struct Fixed(usize);
+
+#[dyn_async]
+impl Counter for Fixed {
+    async fn count(&self) -> usize {
+        self.0
+    }
+}
+
+#[test]
+fn default_methods_without_supertraits() {
+    let mut fixed = Fixed(2);
+    assert_eq!(block_on(fixed.doubled()), 4);
+    assert_eq!(block_on(fixed.reset()), 2);
+
+    let mut counter: Box<dyn Counter + Send + Sync> = Box::new(Fixed(3));
+    let doubled = counter.doubled();
+    assert_send(&doubled);
+    assert_eq!(block_on(doubled), 6);
+    assert_eq!(block_on(counter.reset()), 3);
+}
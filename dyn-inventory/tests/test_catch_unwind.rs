@@ -0,0 +1,126 @@
+#![allow(unused)]
+use dyn_inventory::dyn_inventory;
+
+pub trait Codec {
+    fn name(&self) -> &'static str;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Codec> {
+        pub name: &'static str,
+        handle: Handle
+    };
+    key = name,
+    catch_unwind = true,
+}
+
+mod gzip {
+    use crate::{Codec, PluginInit};
+
+    dyn_inventory::emit! {
+        Handle Codec as Plugin {
+            name = "gzip",
+        }
+    }
+
+    impl Codec for Handle {
+        fn name(&self) -> &'static str {
+            "gzip"
+        }
+    }
+}
+
+mod broken {
+    use crate::{Codec, PluginInit};
+
+    pub struct Handle;
+
+    fn load() -> Handle {
+        panic!("broken codec")
+    }
+
+    dyn_inventory::emit! {
+        Handle Codec as Plugin {
+            name = "broken",
+        };
+        factory = load,
+    }
+
+    impl Codec for Handle {
+        fn name(&self) -> &'static str {
+            "broken"
+        }
+    }
+}
+
+#[test]
+fn panics_are_reported() {
+    let collected = PluginCollector::new();
+    assert_eq!(collected.plugins.len(), 1);
+    assert!(collected.get("gzip").is_some());
+
+    assert_eq!(collected.panicked.len(), 1);
+    let (init, panic) = &collected.panicked[0];
+    assert_eq!(init.name, "broken");
+    assert_eq!(panic.message(), Some("broken codec"));
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LoadError;
+
+dyn_inventory! {
+    AsyncPlugin<Handle: Codec> {
+        pub name: &'static str,
+        handle: Handle
+    };
+    async_factory = true,
+    error = LoadError,
+    catch_unwind = true,
+}
+
+mod remote {
+    use crate::{AsyncPluginInit, Codec, LoadError};
+
+    pub struct Handle;
+
+    async fn fails() -> Result<Handle, LoadError> {
+        Err(LoadError)
+    }
+
+    async fn panics() -> Result<Handle, LoadError> {
+        panic!("remote codec {}", 2)
+    }
+
+    dyn_inventory::emit! {
+        Handle Codec as AsyncPlugin {
+            name = "fails",
+        };
+        factory = fails,
+    }
+
+    dyn_inventory::emit! {
+        Handle Codec as AsyncPlugin {
+            name = "panics",
+        };
+        factory = panics,
+    }
+
+    impl Codec for Handle {
+        fn name(&self) -> &'static str {
+            "remote"
+        }
+    }
+}
+
+#[test]
+fn async_panics_are_reported() {
+    let collected = AsyncPluginCollector::new();
+    assert!(collected.plugins.is_empty());
+
+    assert_eq!(collected.failed.len(), 1);
+    assert_eq!(collected.failed[0].0.name, "fails");
+
+    assert_eq!(collected.panicked.len(), 1);
+    assert_eq!(collected.panicked[0].0.name, "panics");
+    assert_eq!(collected.panicked[0].1.message(), Some("remote codec 2"));
+}
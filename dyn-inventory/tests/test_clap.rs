@@ -0,0 +1,133 @@
+#![allow(unused)]
+use dyn_inventory::dyn_inventory;
+
+pub trait Greeter {
+    fn greet(
+        &self,
+        value: &str,
+    ) -> String;
+}
+
+dyn_inventory! {
+    Flag<Handle: Greeter> {
+        pub flag_name: &'static str,
+        pub desc: &'static str,
+        pub handle: Handle
+    };
+    key = flag_name,
+    clap = flag_name,
+    help = desc,
+}
+
+mod hello {
+    use crate::{FlagInit, Greeter};
+
+    dyn_inventory::emit! {
+        Handle Greeter as Flag {
+            flag_name = "hello",
+            desc = "says hello",
+        }
+    }
+
+    impl Greeter for Handle {
+        fn greet(
+            &self,
+            value: &str,
+        ) -> String {
+            format!("hello, {value}")
+        }
+    }
+}
+
+mod bye {
+    use crate::{FlagInit, Greeter};
+
+    dyn_inventory::emit! {
+        Handle Greeter as Flag {
+            flag_name = "bye",
+            desc = "says goodbye",
+        }
+    }
+
+    impl Greeter for Handle {
+        fn greet(
+            &self,
+            value: &str,
+        ) -> String {
+            format!("goodbye, {value}")
+        }
+    }
+}
+
+fn command(collected: &FlagCollector) -> clap::Command {
+    collected.augment_args(clap::Command::new("greeter"))
+}
+
+#[test]
+fn args() {
+    let collected = FlagCollector::new();
+    let matches = command(&collected)
+        .try_get_matches_from(["greeter", "--hello", "world", "--bye=moon"])
+        .unwrap();
+
+    let mut greeted: Vec<_> = collected
+        .matched_args(&matches)
+        .map(|(flag, value)| flag.handle.greet(value))
+        .collect();
+    greeted.sort();
+    assert_eq!(greeted, ["goodbye, moon", "hello, world"]);
+
+    let matches = command(&collected)
+        .try_get_matches_from(["greeter", "--hello", "world"])
+        .unwrap();
+    let matched: Vec<_> = collected
+        .matched_args(&matches)
+        .map(|(flag, _)| flag.key())
+        .collect();
+    assert_eq!(matched, ["hello"]);
+}
+
+#[test]
+fn missing_value() {
+    let collected = FlagCollector::new();
+    let err = command(&collected)
+        .try_get_matches_from(["greeter", "--hello"])
+        .unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::InvalidValue);
+}
+
+#[test]
+fn help() {
+    let collected = FlagCollector::new();
+    let help = command(&collected).render_help().to_string();
+    assert!(help.contains("--hello <hello>"));
+    assert!(help.contains("says hello"));
+    assert!(help.contains("says goodbye"));
+}
+
+#[test]
+fn subcommands() {
+    let collected = FlagCollector::new();
+    let command = collected.augment_subcommands(
+        clap::Command::new("greeter").arg(clap::Arg::new("name").required(true)),
+    );
+
+    let matches = command
+        .clone()
+        .try_get_matches_from(["greeter", "world", "bye"])
+        .unwrap();
+    let (flag, _) = collected
+        .matched_subcommand(&matches)
+        .unwrap();
+    let name: &String = matches.get_one("name").unwrap();
+    assert_eq!(flag.handle.greet(name), "goodbye, world");
+
+    let matches = command
+        .try_get_matches_from(["greeter", "world"])
+        .unwrap();
+    assert!(
+        collected
+            .matched_subcommand(&matches)
+            .is_none()
+    );
+}
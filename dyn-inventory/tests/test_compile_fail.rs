@@ -1,10 +1,21 @@
+#[test_case::test_case("api_version_invalid.rs"; "api_version is not a semver version")]
+#[test_case::test_case("backend_mismatch.rs"; "emit with a different backend than the registry")]
+#[test_case::test_case("config_without_key.rs"; "config declared without a key")]
+#[test_case::test_case("depends_on_without_key.rs"; "depends_on declared without a key")]
+#[test_case::test_case("downcast_without_any.rs"; "downcast without an Any supertrait")]
+#[test_case::test_case("help_without_clap.rs"; "help declared without a clap field")]
 #[test_case::test_case("init_name_invalid_ident.rs"; "invalid ident in struct position")]
 #[test_case::test_case("init_name_keyword_ident.rs"; "keyword used as macro_name value")]
 #[test_case::test_case("init_name_string_literal.rs"; "string literal where ident expected for macro_name")]
 #[test_case::test_case("is_not_dyn_compat.rs"; "trait not dyn compatible")]
+#[test_case::test_case("key_unknown_field.rs"; "key names an undeclared field")]
+#[test_case::test_case("key_wrong_type.rs"; "key names a field which is not a str")]
 #[test_case::test_case("missing_comma_between_opts.rs"; "missing comma between extraparams")]
 #[test_case::test_case("missing_equals_in_opt.rs"; "missing equals in extraparams entry")]
 #[test_case::test_case("missing_semicolon_before_opts.rs"; "missing semicolon before extraparams list")]
+#[test_case::test_case("missing_handle_field.rs"; "no field has the handle type")]
+#[test_case::test_case("serde_attrs_without_serde.rs"; "serde attributes declared without serde = true")]
+#[test_case::test_case("tags_wrong_type.rs"; "tags names a field which is not a slice of strs")]
 #[test_case::test_case("test_basic_fail.rs"; "unknown extraparams keyword")]
 fn test_failures(path: &'static str) {
     let t = trybuild::TestCases::new();
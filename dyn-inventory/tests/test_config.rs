@@ -0,0 +1,322 @@
+#![allow(unused)]
+use dyn_inventory::{dyn_inventory, mock_plugin};
+use dyn_inventory_runtime::config::{Config, ConfigError};
+use serde::Deserialize;
+use serde_json::json;
+
+pub trait Service {
+    fn describe(&self) -> String;
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Unavailable;
+
+dyn_inventory! {
+    Plugin<Handle: Service> {
+        pub name: &'static str,
+        handle: Handle
+    };
+    key = name,
+    error = Unavailable,
+    config = true,
+}
+
+mod http {
+    use super::*;
+
+    #[derive(Deserialize)]
+    pub struct HttpConfig {
+        port: u16,
+        #[serde(default)]
+        tls: Option<Tls>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Tls {
+        cert: String,
+    }
+
+    pub struct Handle {
+        port: u16,
+        cert: Option<String>,
+    }
+
+    dyn_inventory::emit! {
+        Handle Service as Plugin {
+            name = "http",
+        };
+        factory = |config: HttpConfig| {
+            if config.port == 0 {
+                return Err(Unavailable);
+            }
+            Ok(Handle {
+                port: config.port,
+                cert: config.tls.map(|tls| tls.cert),
+            })
+        },
+    }
+
+    impl Service for Handle {
+        fn describe(&self) -> String {
+            match &self.cert {
+                Some(cert) => format!("https on {} with {cert}", self.port),
+                None => format!("http on {}", self.port),
+            }
+        }
+    }
+}
+
+mod metrics {
+    use super::*;
+
+    #[derive(Deserialize)]
+    pub struct MetricsConfig {
+        #[serde(default = "default_interval")]
+        interval: u64,
+    }
+
+    fn default_interval() -> u64 {
+        60
+    }
+
+    pub struct Handle {
+        interval: u64,
+    }
+
+    dyn_inventory::emit! {
+        Handle Service as Plugin {
+            name = "metrics",
+        };
+        factory = |config: MetricsConfig| Ok(Handle { interval: config.interval }),
+    }
+
+    impl Service for Handle {
+        fn describe(&self) -> String {
+            format!("metrics every {}s", self.interval)
+        }
+    }
+}
+
+mod health {
+    use super::*;
+
+    dyn_inventory::emit! {
+        Handle Service as Plugin {
+            name = "health",
+        }
+    }
+
+    impl Service for Handle {
+        fn describe(&self) -> String {
+            "health".into()
+        }
+    }
+}
+
+fn described(collected: &PluginCollector) -> Vec<String> {
+    let mut described: Vec<_> = collected
+        .plugins
+        .iter()
+        .map(|plugin| plugin.handle.describe())
+        .collect();
+    described.sort();
+    described
+}
+
+#[test]
+fn json() {
+    let collected = PluginCollector::from_config(json!({
+        "http": { "port": 443, "tls": { "cert": "http.pem" } },
+        "metrics": { "interval": 10 },
+    }))
+    .unwrap();
+    assert!(collected.misconfigured.is_empty());
+    assert_eq!(
+        described(&collected),
+        ["health", "https on 443 with http.pem", "metrics every 10s"]
+    );
+
+    // further instances are built with the same section
+    let spawned = collected.spawn("http").unwrap().unwrap();
+    assert_eq!(spawned.handle.describe(), "https on 443 with http.pem");
+}
+
+#[test]
+fn toml() {
+    let config: toml::Table = toml::from_str(
+        r#"
+        [http]
+        port = 8080
+        "#,
+    )
+    .unwrap();
+    let collected = PluginCollector::from_config(config).unwrap();
+    assert_eq!(
+        described(&collected),
+        ["health", "http on 8080", "metrics every 60s"]
+    );
+}
+
+#[test]
+fn invalid_field() {
+    let collected = PluginCollector::from_config(json!({
+        "http": { "port": 443, "tls": { "cert": 1 } },
+    }))
+    .unwrap();
+    assert_eq!(described(&collected), ["health", "metrics every 60s"]);
+
+    let [(init, err)] = &collected.misconfigured[..] else {
+        panic!("expected one misconfigured plugin");
+    };
+    assert_eq!(init.name, "http");
+    assert_eq!(
+        *err,
+        ConfigError::Invalid {
+            plugin: "http",
+            field: Some("tls.cert".into()),
+            message: "invalid type: integer `1`, expected a string".into(),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "invalid configuration of plugin `http` at `tls.cert`: invalid type: integer `1`, expected a string"
+    );
+}
+
+#[test]
+fn missing_section() {
+    // without a config, every plugin is configured from an empty table
+    let collected = PluginCollector::new();
+    assert_eq!(described(&collected), ["health", "metrics every 60s"]);
+    let [(_, err)] = &collected.misconfigured[..] else {
+        panic!("expected one misconfigured plugin");
+    };
+    assert_eq!(
+        err.to_string(),
+        "invalid configuration of plugin `http`: missing field `port`"
+    );
+}
+
+#[test]
+fn unknown_section() {
+    let err = PluginCollector::from_config(json!({
+        "http": { "port": 443 },
+        "metric": { "interval": 10 },
+    }))
+    .err()
+    .unwrap();
+    assert_eq!(
+        err,
+        ConfigError::Unknown {
+            name: "metric".into()
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "the configuration has a section for `metric`, which is not a registered plugin"
+    );
+}
+
+#[test]
+fn not_a_table() {
+    let err = PluginCollector::from_config(json!(["http"]))
+        .err()
+        .unwrap();
+    assert_eq!(err, ConfigError::NotATable { found: "array" });
+}
+
+#[test]
+fn factory_fails_after_configuration() {
+    let collected = PluginCollector::from_config(json!({ "http": { "port": 0 } })).unwrap();
+    assert!(collected.misconfigured.is_empty());
+    let [(init, err)] = &collected.failed[..] else {
+        panic!("expected one failed plugin");
+    };
+    assert_eq!((init.name, err), ("http", &Unavailable));
+}
+
+#[test]
+fn mocked() {
+    #[derive(Deserialize)]
+    struct Greeting {
+        text: String,
+    }
+
+    struct Greeter(String);
+
+    impl Service for Greeter {
+        fn describe(&self) -> String {
+            self.0.clone()
+        }
+    }
+
+    let mock = mock_plugin! {
+        Plugin {
+            name = "greeter",
+        };
+        factory = |config: Greeting| Ok(Greeter(config.text)),
+    };
+    let section = json!({ "text": "hello" }).into();
+    let plugin = mock.configure(section).unwrap()().unwrap();
+    assert_eq!(plugin.handle.describe(), "hello");
+}
+
+mod pooled {
+    use super::*;
+
+    #[dyn_inventory::dyn_async]
+    pub trait Pool {
+        async fn size(&self) -> usize;
+    }
+
+    dyn_inventory::dyn_inventory! {
+        Pooled<Handle: Pool> {
+            pub name: &'static str,
+            pub handle: Handle
+        };
+        key = name,
+        async_factory = true,
+        config = true,
+    }
+
+    #[derive(Deserialize)]
+    pub struct PoolConfig {
+        size: usize,
+    }
+
+    pub struct Handle {
+        size: usize,
+    }
+
+    dyn_inventory::emit! {
+        Handle Pool as Pooled {
+            name = "bounded",
+        };
+        factory = |config: PoolConfig| async move { Handle { size: config.size } },
+    }
+
+    #[dyn_inventory::dyn_async]
+    impl Pool for Handle {
+        async fn size(&self) -> usize {
+            self.size
+        }
+    }
+}
+
+#[test]
+fn async_factory() {
+    let collected =
+        pooled::PooledCollector::from_config(json!({ "bounded": { "size": 4 } })).unwrap();
+    let bounded = collected.get("bounded").unwrap();
+    assert_eq!(dyn_inventory_runtime::block_on(bounded.handle.size()), 4);
+
+    let collected = pooled::PooledCollector::new();
+    assert!(collected.plugins.is_empty());
+    assert!(matches!(
+        collected.misconfigured[0].1,
+        ConfigError::Invalid {
+            plugin: "bounded",
+            ..
+        }
+    ));
+}
@@ -0,0 +1,197 @@
+#![allow(unused)]
+use dyn_inventory::dyn_inventory;
+
+pub trait Service {
+    fn start(&self) -> &'static str;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Service> {
+        pub name: &'static str,
+        pub depends_on: &'static [&'static str],
+        handle: Handle
+    };
+    key = name,
+    depends_on = depends_on,
+}
+
+mod http {
+    use crate::{PluginInit, Service};
+
+    dyn_inventory::emit! {
+        Handle Service as Plugin {
+            name = "http",
+            depends_on = &["metrics", "config"],
+        }
+    }
+
+    impl Service for Handle {
+        fn start(&self) -> &'static str {
+            "http"
+        }
+    }
+}
+
+mod metrics {
+    use crate::{PluginInit, Service};
+
+    dyn_inventory::emit! {
+        Handle Service as Plugin {
+            name = "metrics",
+            depends_on = &["config"],
+        }
+    }
+
+    impl Service for Handle {
+        fn start(&self) -> &'static str {
+            "metrics"
+        }
+    }
+}
+
+mod config {
+    use crate::{PluginInit, Service};
+
+    dyn_inventory::emit! {
+        Handle Service as Plugin {
+            name = "config",
+            depends_on = &[],
+        }
+    }
+
+    impl Service for Handle {
+        fn start(&self) -> &'static str {
+            "config"
+        }
+    }
+}
+
+struct Fake;
+
+impl Service for Fake {
+    fn start(&self) -> &'static str {
+        "fake"
+    }
+}
+
+fn fake(
+    name: &'static str,
+    depends_on: &'static [&'static str],
+) -> PluginInit {
+    dyn_inventory::mock_plugin! {
+        Plugin {
+            name = name,
+            depends_on = depends_on,
+        };
+        handle = Fake,
+    }
+}
+
+fn keys(inits: &[PluginInit]) -> Vec<&'static str> {
+    inits.iter().map(|init| init.name).collect()
+}
+
+#[test]
+fn e2e() {
+    let collected = PluginCollector::new();
+    let started: Vec<_> = collected
+        .plugins
+        .iter()
+        .map(|plugin| plugin.handle.start())
+        .collect();
+    assert_eq!(started, ["config", "metrics", "http"]);
+    assert_eq!(collected.get("metrics").unwrap().key(), "metrics");
+    assert!(collected.get("missing").is_none());
+}
+
+#[test]
+fn keeps_registration_order_for_independent_plugins() {
+    let ordered =
+        PluginCollector::order(vec![fake("b", &[]), fake("a", &[]), fake("c", &["a"])]).unwrap();
+    assert_eq!(keys(&ordered), ["b", "a", "c"]);
+}
+
+#[test]
+fn missing_dependency() {
+    let err = PluginCollector::order(vec![fake("http", &["metrics"])])
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        PluginDependencyError::Missing {
+            plugin: "http",
+            dependency: "metrics"
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "plugin `http` depends on `metrics`, which is not registered"
+    );
+}
+
+#[test]
+fn duplicate_key() {
+    let mut second = fake("a", &[]);
+    second.__source.line = 1;
+    let err = PluginCollector::order(vec![fake("a", &[]), second])
+        .err()
+        .unwrap();
+    let PluginDependencyError::Duplicate {
+        plugin,
+        first,
+        second,
+    } = &err
+    else {
+        panic!("expected a duplicate, got {err:?}");
+    };
+    assert_eq!(*plugin, "a");
+    assert_eq!(first.module_path, "test_dependencies");
+    assert_eq!(second.line, 1);
+    assert!(err.to_string().starts_with(
+        "plugin `a` is registered more than once, by `test_dependencies` (dyn-inventory "
+    ));
+}
+
+#[test]
+fn cycle() {
+    let err = PluginCollector::order(vec![
+        fake("root", &["a"]),
+        fake("a", &["b"]),
+        fake("b", &["c"]),
+        fake("c", &["a"]),
+    ])
+    .err()
+    .unwrap();
+    assert_eq!(
+        err,
+        PluginDependencyError::Cycle {
+            plugins: vec!["a", "b", "c"]
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "dependency cycle: `a` -> `b` -> `c` -> `a`"
+    );
+}
+
+#[test]
+fn deselected_dependency() {
+    let err = PluginCollector::from_selection(&[], &["metrics"])
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        PluginSelectionError::Dependency(PluginDependencyError::Missing {
+            plugin: "http",
+            dependency: "metrics"
+        })
+    );
+
+    let collected = PluginCollector::from_selection(&["config", "metrics"], &[]).unwrap();
+    let keys: Vec<_> = collected
+        .plugins
+        .iter()
+        .map(Plugin::key)
+        .collect();
+    assert_eq!(keys, ["config", "metrics"]);
+}
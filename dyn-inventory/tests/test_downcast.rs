@@ -0,0 +1,88 @@
+#![allow(unused)]
+use std::any::Any;
+
+use dyn_inventory::dyn_inventory;
+
+pub trait Codec: Any {
+    fn name(&self) -> &'static str;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Codec> {
+        handle: Handle
+    };
+    downcast = true,
+}
+
+pub mod gzip {
+    use crate::{Codec, PluginInit};
+
+    dyn_inventory::emit! {
+        pub Handle Codec as Plugin {}
+    }
+
+    impl Handle {
+        pub fn level(&self) -> u32 {
+            6
+        }
+    }
+
+    impl Codec for Handle {
+        fn name(&self) -> &'static str {
+            "gzip"
+        }
+    }
+}
+
+pub mod zstd {
+    use crate::{Codec, PluginInit};
+
+    dyn_inventory::emit! {
+        pub Handle Codec as Plugin {}
+    }
+
+    impl Codec for Handle {
+        fn name(&self) -> &'static str {
+            "zstd"
+        }
+    }
+}
+
+struct Unregistered;
+
+#[test]
+fn downcast() {
+    let collected = PluginCollector::new();
+    let plugin = collected
+        .find_by_type::<gzip::Handle>()
+        .unwrap();
+    assert_eq!(plugin.handle.name(), "gzip");
+    assert!(plugin.is::<gzip::Handle>());
+    assert!(!plugin.is::<zstd::Handle>());
+    assert_eq!(
+        plugin
+            .downcast_ref::<gzip::Handle>()
+            .unwrap()
+            .level(),
+        6
+    );
+    assert!(
+        plugin
+            .downcast_ref::<zstd::Handle>()
+            .is_none()
+    );
+
+    assert_eq!(
+        collected
+            .find_by_type::<zstd::Handle>()
+            .unwrap()
+            .handle
+            .name(),
+        "zstd"
+    );
+    assert!(
+        collected
+            .find_by_type::<Unregistered>()
+            .is_none()
+    );
+}
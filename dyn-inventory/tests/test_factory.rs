@@ -0,0 +1,49 @@
+#![allow(unused)]
+use dyn_inventory::dyn_inventory;
+
+pub trait Device {
+    fn path(&self) -> &str;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Device> {
+        pub name: &'static str,
+        handle: Handle
+    };
+}
+
+mod serial {
+    use crate::{Device, PluginInit};
+
+    pub struct Handle {
+        path: String,
+    }
+
+    impl Handle {
+        fn open() -> Self {
+            Self {
+                path: "/dev/ttyS0".into(),
+            }
+        }
+    }
+
+    dyn_inventory::emit! {
+        Handle Device as Plugin {
+            name = "serial",
+        };
+        factory = Handle::open,
+    }
+
+    impl Device for Handle {
+        fn path(&self) -> &str {
+            &self.path
+        }
+    }
+}
+
+#[test]
+fn factory() {
+    let collected = PluginCollector::new();
+    assert_eq!(collected.plugins.len(), 1);
+    assert_eq!(collected.plugins[0].handle.path(), "/dev/ttyS0");
+}
@@ -0,0 +1,164 @@
+#![allow(unused)]
+use dyn_inventory::dyn_inventory;
+
+pub trait Device {
+    fn path(&self) -> &str;
+}
+
+#[derive(Debug, PartialEq)]
+pub struct OpenError(&'static str);
+
+dyn_inventory! {
+    Plugin<Handle: Device> {
+        pub name: &'static str,
+        handle: Handle
+    };
+    key = name,
+    error = OpenError,
+}
+
+mod serial {
+    use crate::{Device, OpenError, PluginInit};
+
+    pub struct Handle;
+
+    impl Handle {
+        fn open() -> Result<Self, OpenError> {
+            Ok(Self)
+        }
+    }
+
+    dyn_inventory::emit! {
+        Handle Device as Plugin {
+            name = "serial",
+        };
+        factory = Handle::open,
+    }
+
+    impl Device for Handle {
+        fn path(&self) -> &str {
+            "/dev/ttyS0"
+        }
+    }
+}
+
+mod usb {
+    use crate::{Device, OpenError, PluginInit};
+
+    pub struct Handle;
+
+    impl Handle {
+        fn open() -> Result<Self, OpenError> {
+            Err(OpenError("/dev/ttyUSB0 does not exist"))
+        }
+    }
+
+    dyn_inventory::emit! {
+        Handle Device as Plugin {
+            name = "usb",
+        };
+        factory = Handle::open,
+    }
+
+    impl Device for Handle {
+        fn path(&self) -> &str {
+            "/dev/ttyUSB0"
+        }
+    }
+}
+
+mod null {
+    use crate::{Device, PluginInit};
+
+    dyn_inventory::emit! {
+        Handle Device as Plugin {
+            name = "null",
+        }
+    }
+
+    impl Device for Handle {
+        fn path(&self) -> &str {
+            "/dev/null"
+        }
+    }
+}
+
+#[test]
+fn failures_are_reported() {
+    let collected = PluginCollector::new();
+
+    let mut loaded: Vec<_> = collected
+        .plugins
+        .iter()
+        .map(Plugin::key)
+        .collect();
+    loaded.sort();
+    assert_eq!(loaded, ["null", "serial"]);
+
+    assert_eq!(collected.failed.len(), 1);
+    let (init, err) = &collected.failed[0];
+    assert_eq!(init.name, "usb");
+    assert_eq!(*err, OpenError("/dev/ttyUSB0 does not exist"));
+}
+
+#[test]
+fn instantiate() {
+    let usb = PluginInit::iter()
+        .find(|init| init.name == "usb")
+        .unwrap();
+    assert!(usb.clone().instantiate().is_err());
+
+    let serial = PluginInit::iter()
+        .find(|init| init.name == "serial")
+        .unwrap();
+    assert_eq!(
+        serial
+            .clone()
+            .instantiate()
+            .unwrap()
+            .handle
+            .path(),
+        "/dev/ttyS0"
+    );
+}
+
+dyn_inventory! {
+    AsyncPlugin<Handle: Device> {
+        pub name: &'static str,
+        handle: Handle
+    };
+    async_factory = true,
+    error = OpenError,
+}
+
+mod remote {
+    use crate::{AsyncPluginInit, Device, OpenError};
+
+    pub struct Handle;
+
+    async fn connect() -> Result<Handle, OpenError> {
+        Err(OpenError("connection refused"))
+    }
+
+    dyn_inventory::emit! {
+        Handle Device as AsyncPlugin {
+            name = "remote",
+        };
+        factory = connect,
+    }
+
+    impl Device for Handle {
+        fn path(&self) -> &str {
+            "tcp://localhost"
+        }
+    }
+}
+
+#[test]
+fn async_failures_are_reported() {
+    let collected = dyn_inventory_runtime::block_on(AsyncPluginCollector::new_async());
+    assert!(collected.plugins.is_empty());
+    assert_eq!(collected.failed.len(), 1);
+    assert_eq!(collected.failed[0].0.name, "remote");
+    assert_eq!(collected.failed[0].1, OpenError("connection refused"));
+}
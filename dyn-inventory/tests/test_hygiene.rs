@@ -0,0 +1,130 @@
+#![allow(unused)]
+
+// generated code only uses fully qualified paths, so it is unaffected by items which shadow the
+// prelude
+mod shadowed {
+    #![allow(non_camel_case_types, dead_code)]
+
+    pub struct Box;
+    pub struct Vec;
+    pub struct String;
+    pub struct Option;
+    pub struct Result;
+    pub struct Some;
+    pub struct None;
+    pub struct Ok;
+    pub struct Err;
+    pub trait Clone {}
+    pub trait Default {}
+    pub trait From {}
+    pub trait Iterator {}
+    pub trait IntoIterator {}
+    pub trait Fn {}
+    pub trait Send {}
+    pub mod std {}
+    pub mod alloc {}
+
+    macro_rules! vec {
+        ($($tt:tt)*) => {
+            ::core::compile_error!("shadowed")
+        };
+    }
+
+    macro_rules! write {
+        ($($tt:tt)*) => {
+            ::core::compile_error!("shadowed")
+        };
+    }
+
+    macro_rules! panic {
+        ($($tt:tt)*) => {
+            ::core::compile_error!("shadowed")
+        };
+    }
+
+    pub trait Greeter {
+        fn greet(&self) -> &'static str;
+    }
+
+    #[derive(Debug)]
+    pub struct Refused;
+
+    dyn_inventory::dyn_inventory! {
+        Plugin<Handle: Greeter> {
+            pub name: &'static str,
+            pub depends_on: &'static [&'static str],
+            pub tags: &'static [&'static str],
+            pub handle: Handle
+        };
+        key = name,
+        depends_on = depends_on,
+        env_prefix = "HYGIENE",
+        tags = tags,
+        error = Refused,
+        api_version = "1.0",
+        catch_unwind = true,
+    }
+
+    dyn_inventory::emit! {
+        Handle Greeter as Plugin {
+            name = "hello",
+            depends_on = &[],
+            tags = &["greeting"],
+        }
+    }
+
+    impl Greeter for Handle {
+        fn greet(&self) -> &'static str {
+            "hello"
+        }
+    }
+
+    #[dyn_inventory::dyn_async]
+    pub trait Fetcher {
+        async fn fetch(&self) -> u8;
+    }
+
+    dyn_inventory::dyn_inventory! {
+        Remote<Handle: Fetcher> {
+            pub name: &'static str,
+            pub handle: Handle
+        };
+        key = name,
+        async_factory = true,
+        error = Refused,
+    }
+
+    pub mod remote {
+        use super::{Fetcher, RemoteInit};
+
+        dyn_inventory::emit! {
+            Handle Fetcher as Remote {
+                name = "remote",
+            }
+        }
+
+        #[dyn_inventory::dyn_async]
+        impl Fetcher for Handle {
+            async fn fetch(&self) -> u8 {
+                1
+            }
+        }
+    }
+}
+
+#[test]
+fn collect() {
+    let collected = shadowed::PluginCollector::new();
+    assert_eq!(
+        collected
+            .get("hello")
+            .unwrap()
+            .handle
+            .greet(),
+        "hello"
+    );
+
+    let remote = shadowed::RemoteCollector::new();
+    let fetched = dyn_inventory_runtime::block_on(remote.get("remote").unwrap().handle.fetch());
+    assert_eq!(fetched, 1);
+}
@@ -41,3 +41,14 @@ fn e2e() {
         assert_eq!(plugin.name, "my plugin for abc-framework");
     }
 }
+
+#[test]
+fn source() {
+    let collected = PluginCollector::new();
+    let source = collected.plugins[0].source();
+    assert_eq!(source.module_path, "test_inventory::my_plugin");
+    assert!(source.file.ends_with("test_inventory.rs"));
+    assert_eq!(source.line, 21);
+    assert_eq!(source.package, "dyn-inventory");
+    assert_eq!(source.version, env!("CARGO_PKG_VERSION"));
+}
@@ -0,0 +1,182 @@
+#![allow(unused)]
+use std::{cell::RefCell, fmt};
+
+use dyn_inventory::{dyn_inventory, mock_plugin};
+use dyn_inventory_runtime::{HookError, Lifecycle};
+
+pub trait Service: Lifecycle {
+    fn name(&self) -> &'static str;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Service> {
+        pub name: &'static str,
+        pub depends_on: &'static [&'static str],
+        pub priority: i32,
+        handle: Handle
+    };
+    key = name,
+    depends_on = depends_on,
+    priority = priority,
+    lifecycle = true,
+}
+
+thread_local! {
+    static LOG: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn log(event: String) {
+    LOG.with(|log| log.borrow_mut().push(event));
+}
+
+/// the events logged so far, clearing the log
+fn logged() -> Vec<String> {
+    LOG.with(|log| log.take())
+}
+
+#[derive(Debug)]
+struct Unreachable;
+
+impl fmt::Display for Unreachable {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str("unreachable")
+    }
+}
+
+impl std::error::Error for Unreachable {}
+
+struct Recorder {
+    name: &'static str,
+    fails: bool,
+}
+
+impl Lifecycle for Recorder {
+    fn on_load(&mut self) -> Result<(), HookError> {
+        log(format!("load {}", self.name));
+        if self.fails {
+            return Err(Box::new(Unreachable));
+        }
+        Ok(())
+    }
+
+    fn on_unload(&mut self) {
+        log(format!("unload {}", self.name));
+    }
+}
+
+impl Service for Recorder {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// a registration of a recorder, whose name is a literal so that its factory captures nothing
+macro_rules! plugin {
+    ($name:literal, $depends_on:expr, $priority:literal) => {
+        plugin!($name, $depends_on, $priority, false)
+    };
+    ($name:literal, $depends_on:expr, $priority:literal, $fails:literal) => {
+        mock_plugin! {
+            Plugin {
+                name = $name,
+                depends_on = $depends_on,
+                priority = $priority,
+            };
+            handle = Recorder { name: $name, fails: $fails },
+        }
+    };
+}
+
+fn names(plugins: &[Plugin]) -> Vec<&'static str> {
+    plugins.iter().map(Plugin::key).collect()
+}
+
+#[test]
+fn starts_by_priority_and_dependencies() {
+    let collected = PluginCollector::from_inits(vec![
+        plugin!("http", &["db"], 0),
+        plugin!("db", &[], 5),
+        plugin!("config", &[], -1),
+        plugin!("metrics", &[], 1),
+    ])
+    .unwrap();
+    // `db` has the lowest priority of the rest, but starts before `http`, which depends on it
+    assert_eq!(
+        names(&collected.plugins),
+        ["config", "db", "http", "metrics"]
+    );
+
+    let managed = collected.start().unwrap();
+    assert_eq!(
+        names(managed.plugins()),
+        ["config", "db", "http", "metrics"]
+    );
+    assert_eq!(managed.get("db").unwrap().handle.name(), "db");
+    assert_eq!(
+        logged(),
+        ["load config", "load db", "load http", "load metrics"]
+    );
+
+    drop(managed);
+    assert_eq!(
+        logged(),
+        [
+            "unload metrics",
+            "unload http",
+            "unload db",
+            "unload config"
+        ]
+    );
+}
+
+#[test]
+fn shutdown_stops_once() {
+    let mut managed = PluginCollector::from_inits(vec![plugin!("a", &[], 0), plugin!("b", &[], 0)])
+        .unwrap()
+        .start()
+        .unwrap();
+    logged();
+
+    assert!(managed.is_running());
+    managed.shutdown();
+    assert_eq!(logged(), ["unload b", "unload a"]);
+    assert!(managed.plugins().is_empty());
+    assert!(managed.get("a").is_none());
+    assert!(!managed.is_running());
+
+    managed.shutdown();
+    drop(managed);
+    assert!(logged().is_empty());
+}
+
+#[test]
+fn failed_start_stops_started_plugins() {
+    let err = PluginCollector::from_inits(vec![
+        plugin!("a", &[], 0),
+        plugin!("b", &[], 0),
+        plugin!("c", &[], 0, true),
+        plugin!("d", &[], 0),
+    ])
+    .unwrap()
+    .start()
+    .err()
+    .unwrap();
+
+    assert_eq!(
+        logged(),
+        ["load a", "load b", "load c", "unload b", "unload a"]
+    );
+    assert_eq!(err.key, Some("c"));
+    assert_eq!(err.source.module_path, "test_lifecycle");
+    assert_eq!(err.stopped, 2);
+    assert_eq!(err.to_string(), "plugin `c` failed to start: unreachable");
+    assert_eq!(
+        std::error::Error::source(&err)
+            .unwrap()
+            .to_string(),
+        "unreachable"
+    );
+}
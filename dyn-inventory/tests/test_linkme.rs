@@ -0,0 +1,78 @@
+#![allow(unused)]
+use dyn_inventory::dyn_inventory;
+
+pub trait Greeter {
+    fn greet(&self) -> String;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Greeter> {
+        pub name: &'static str,
+        handle: Handle
+    };
+    key = name,
+    backend = linkme,
+}
+
+mod hello {
+    use crate::{Greeter, PluginInit};
+
+    dyn_inventory::emit! {
+        Handle Greeter as Plugin {
+            name = "hello",
+        };
+        backend = linkme,
+    }
+
+    impl Greeter for Handle {
+        fn greet(&self) -> String {
+            "hello".into()
+        }
+    }
+}
+
+mod hola {
+    use crate::{Greeter, PluginInit};
+
+    pub struct Handle(&'static str);
+
+    dyn_inventory::emit! {
+        Handle Greeter as Plugin {
+            name = "hola",
+        };
+        factory = || Handle("hola"),
+        backend = linkme,
+    }
+
+    impl Greeter for Handle {
+        fn greet(&self) -> String {
+            self.0.into()
+        }
+    }
+}
+
+#[test]
+fn distributed_slice() {
+    let mut names: Vec<_> = PluginInit
+        .iter()
+        .map(|init| init.name)
+        .collect();
+    names.sort();
+    assert_eq!(names, ["hello", "hola"]);
+    assert_eq!(PluginInit::iter().count(), 2);
+}
+
+#[test]
+fn collect() {
+    let collected = PluginCollector::new();
+    assert_eq!(collected.plugins.len(), 2);
+    assert_eq!(collected.get("hola").unwrap().handle.greet(), "hola");
+    assert_eq!(
+        collected
+            .get("hello")
+            .unwrap()
+            .source()
+            .module_path,
+        "test_linkme::hello"
+    );
+}
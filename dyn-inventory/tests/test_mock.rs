@@ -0,0 +1,123 @@
+#![allow(unused)]
+use dyn_inventory::{dyn_inventory, mock_plugin};
+
+pub trait Greeter {
+    fn greet(&self) -> String;
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GreetError;
+
+dyn_inventory! {
+    Plugin<Handle: Greeter> {
+        pub name: &'static str,
+        handle: Handle
+    };
+    key = name,
+    error = GreetError,
+}
+
+mod registered {
+    use crate::{Greeter, PluginInit};
+
+    dyn_inventory::emit! {
+        Handle Greeter as Plugin {
+            name = "registered",
+        }
+    }
+
+    impl Greeter for Handle {
+        fn greet(&self) -> String {
+            "registered".into()
+        }
+    }
+}
+
+#[derive(Default)]
+struct Double {
+    greeting: &'static str,
+}
+
+impl Greeter for Double {
+    fn greet(&self) -> String {
+        self.greeting.into()
+    }
+}
+
+struct Echo(String);
+
+impl Greeter for Echo {
+    fn greet(&self) -> String {
+        self.0.clone()
+    }
+}
+
+#[test]
+fn from_inits() {
+    let collected = PluginCollector::from_inits([
+        mock_plugin! {
+            Plugin {
+                name = "hello",
+            };
+            handle = Double { greeting: "hello" },
+        },
+        mock_plugin! {
+            Plugin {
+                name = "broken",
+            };
+            factory = || Err::<Double, _>(GreetError),
+        },
+    ]);
+
+    assert!(collected.get("registered").is_none());
+    assert_eq!(collected.plugins.len(), 1);
+    assert_eq!(
+        collected
+            .get("hello")
+            .unwrap()
+            .handle
+            .greet(),
+        "hello"
+    );
+    assert_eq!(collected.failed.len(), 1);
+    assert_eq!(collected.failed[0].0.name, "broken");
+}
+
+#[test]
+fn mock_source() {
+    let mock = mock_plugin! {
+        Plugin {
+            name = "default",
+        };
+        factory = || Ok(Double::default()),
+    };
+    assert_eq!(mock.source().module_path, "test_mock");
+    assert_eq!(mock.api_version(), None);
+}
+
+#[test]
+fn captured_state() {
+    let greeting = String::from("captured");
+    let from_factory = greeting.clone();
+    let collected = PluginCollector::from_inits([
+        mock_plugin! {
+            Plugin {
+                name = "handle",
+            };
+            handle = Echo(greeting.clone()),
+        },
+        mock_plugin! {
+            Plugin {
+                name = "factory",
+            };
+            factory = move || Ok(Echo(from_factory.clone())),
+        },
+    ]);
+
+    assert_eq!(collected.failed.len(), 0);
+    for name in ["handle", "factory"] {
+        let plugin = collected.get(name).unwrap();
+        assert_eq!(plugin.handle.greet(), "captured");
+        assert_eq!(plugin.new_instance().unwrap().handle.greet(), "captured");
+    }
+}
@@ -0,0 +1,135 @@
+#![allow(unused)]
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use dyn_inventory::{dyn_inventory, mock_plugin};
+
+pub trait Command {
+    fn run(&self) -> String;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Command> {
+        pub name: &'static str,
+        handle: Handle
+    };
+    key = name,
+}
+
+mod builtin {
+    use crate::{Command, PluginInit};
+
+    dyn_inventory::emit! {
+        Handle Command as Plugin {
+            name = "builtin",
+        }
+    }
+
+    impl Command for Handle {
+        fn run(&self) -> String {
+            "builtin".into()
+        }
+    }
+}
+
+struct Script(String);
+
+impl Command for Script {
+    fn run(&self) -> String {
+        format!("script {}", self.0)
+    }
+}
+
+fn script(
+    name: &'static str,
+    version: &str,
+) -> PluginInit {
+    let version = version.to_owned();
+    mock_plugin! {
+        Plugin {
+            name = name,
+        };
+        handle = Script(version.clone()),
+    }
+}
+
+#[test]
+fn register_and_unregister() {
+    let registry = PluginRegistry::new();
+    assert_eq!(registry.keys(), ["builtin"]);
+
+    assert!(
+        registry
+            .register(script("deploy", "v1"))
+            .is_none()
+    );
+    assert!(registry.contains("deploy"));
+
+    let collected = registry.collect();
+    assert_eq!(collected.plugins.len(), 2);
+    assert_eq!(collected.get("deploy").unwrap().handle.run(), "script v1");
+
+    let replaced = registry.register(script("deploy", "v2"));
+    assert_eq!(replaced.unwrap().name, "deploy");
+    assert_eq!(
+        registry
+            .collect()
+            .get("deploy")
+            .unwrap()
+            .handle
+            .run(),
+        "script v2"
+    );
+
+    assert!(registry.unregister("builtin").is_some());
+    assert!(registry.unregister("builtin").is_none());
+    assert_eq!(registry.keys(), ["deploy"]);
+
+    // collectors built earlier are unaffected
+    assert!(collected.get("builtin").is_some());
+}
+
+#[test]
+fn shared_between_threads() {
+    let registry = Arc::new(PluginRegistry::from_inits([]));
+    let handles: Vec<_> = ["a", "b", "c"]
+        .into_iter()
+        .map(|name| {
+            let registry = registry.clone();
+            thread::spawn(move || registry.register(script(name, "v1")))
+        })
+        .collect();
+    for handle in handles {
+        assert!(handle.join().unwrap().is_none());
+    }
+
+    let mut keys = registry.keys();
+    keys.sort();
+    assert_eq!(keys, ["a", "b", "c"]);
+}
+
+#[test]
+fn captured_state() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let registry = PluginRegistry::from_inits([]);
+    registry.register({
+        let runs = runs.clone();
+        mock_plugin! {
+            Plugin {
+                name = "counted",
+            };
+            handle = Script(format!("run {}", runs.fetch_add(1, Ordering::Relaxed))),
+        }
+    });
+
+    let collected = registry.collect();
+    let counted = collected.get("counted").unwrap();
+    assert_eq!(counted.handle.run(), "script run 0");
+    assert_eq!(counted.new_instance().handle.run(), "script run 1");
+    assert_eq!(runs.load(Ordering::Relaxed), 2);
+}
@@ -0,0 +1,129 @@
+#![allow(unused)]
+use dyn_inventory::dyn_inventory;
+use dyn_inventory_runtime::{Entry, Registry};
+
+pub trait Greeter {
+    fn greet(&self) -> &'static str;
+}
+
+pub trait Exporter {
+    fn export(&self) -> &'static str;
+}
+
+dyn_inventory! {
+    GreeterPlugin<Handle: Greeter> {
+        pub name: &'static str,
+        greeter: Handle
+    };
+}
+
+dyn_inventory! {
+    ExporterPlugin<Handle: Exporter> {
+        exporter: Handle
+    };
+}
+
+pub trait Scheduler {}
+
+dyn_inventory! {
+    SchedulerPlugin<Handle: Scheduler> {
+        pub name: &'static str,
+        pub depends_on: &'static [&'static str],
+        scheduler: Handle
+    };
+    key = name,
+    depends_on = depends_on,
+}
+
+mod cron {
+    use crate::{Scheduler, SchedulerPluginInit};
+
+    dyn_inventory::emit! {
+        Handle Scheduler as SchedulerPlugin {
+            name = "cron",
+            depends_on = &["clock"],
+        }
+    }
+
+    impl Scheduler for Handle {}
+}
+
+mod hello {
+    use crate::{Greeter, GreeterPluginInit};
+
+    dyn_inventory::emit! {
+        Handle Greeter as GreeterPlugin {
+            name = "hello",
+        }
+    }
+
+    impl Greeter for Handle {
+        fn greet(&self) -> &'static str {
+            "hello"
+        }
+    }
+}
+
+mod otlp {
+    use crate::{Exporter, ExporterPluginInit};
+
+    dyn_inventory::emit! {
+        Handle Exporter as ExporterPlugin {}
+    }
+
+    impl Exporter for Handle {
+        fn export(&self) -> &'static str {
+            "otlp"
+        }
+    }
+}
+
+fn count<R: Registry>() -> usize {
+    R::collect().len()
+}
+
+fn first_handle<R: Registry, T>(with: impl Fn(&R::Handle) -> T) -> T {
+    with(R::collect().first().unwrap().handle())
+}
+
+#[test]
+fn generic_over_registries() {
+    assert_eq!(count::<GreeterPluginCollector>(), 1);
+    assert_eq!(count::<ExporterPluginCollector>(), 1);
+
+    assert_eq!(
+        first_handle::<GreeterPluginCollector, _>(|greeter| greeter.greet()),
+        "hello"
+    );
+    assert_eq!(
+        first_handle::<ExporterPluginCollector, _>(|exporter| exporter.export()),
+        "otlp"
+    );
+}
+
+#[test]
+fn try_collect() {
+    assert_eq!(
+        GreeterPluginCollector::try_collect()
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let err = SchedulerPluginCollector::try_collect()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        SchedulerPluginDependencyError::Missing {
+            plugin: "cron",
+            dependency: "clock",
+        }
+    );
+}
+
+#[test]
+#[should_panic = "plugin `cron` depends on `clock`, which is not registered"]
+fn collect_panics_on_dependency_error() {
+    count::<SchedulerPluginCollector>();
+}
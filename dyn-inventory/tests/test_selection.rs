@@ -0,0 +1,127 @@
+#![allow(unused)]
+use dyn_inventory::dyn_inventory;
+
+pub trait Exporter {
+    fn export(&self) -> &'static str;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Exporter> {
+        pub name: &'static str,
+        handle: Handle
+    };
+    key = name,
+    env_prefix = "SELECTION_TEST",
+}
+
+mod otlp {
+    use crate::{Exporter, PluginInit};
+
+    dyn_inventory::emit! {
+        Handle Exporter as Plugin {
+            name = "otlp",
+        }
+    }
+
+    impl Exporter for Handle {
+        fn export(&self) -> &'static str {
+            "otlp"
+        }
+    }
+}
+
+mod stdout {
+    use crate::{Exporter, PluginInit};
+
+    dyn_inventory::emit! {
+        Handle Exporter as Plugin {
+            name = "stdout",
+        }
+    }
+
+    impl Exporter for Handle {
+        fn export(&self) -> &'static str {
+            "stdout"
+        }
+    }
+}
+
+mod prometheus {
+    use crate::{Exporter, PluginInit};
+
+    dyn_inventory::emit! {
+        Handle Exporter as Plugin {
+            name = "prometheus",
+        }
+    }
+
+    impl Exporter for Handle {
+        fn export(&self) -> &'static str {
+            "prometheus"
+        }
+    }
+}
+
+fn keys(collector: &PluginCollector) -> Vec<&'static str> {
+    let mut keys: Vec<_> = collector
+        .plugins
+        .iter()
+        .map(Plugin::key)
+        .collect();
+    keys.sort();
+    keys
+}
+
+#[test_case::test_case(&[], &[], &["otlp", "prometheus", "stdout"]; "empty selection collects everything")]
+#[test_case::test_case(&["otlp", "stdout"], &[], &["otlp", "stdout"]; "allow list")]
+#[test_case::test_case(&[], &["stdout"], &["otlp", "prometheus"]; "deny list")]
+#[test_case::test_case(&["otlp", "stdout"], &["stdout"], &["otlp"]; "deny wins over allow")]
+fn from_selection(
+    allow: &[&str],
+    deny: &[&str],
+    expected: &[&str],
+) {
+    let collected = PluginCollector::from_selection(allow, deny).unwrap();
+    assert_eq!(keys(&collected), expected);
+}
+
+#[test_case::test_case(&["otlp", "stdot"], &[]; "typo in allow list")]
+#[test_case::test_case(&[], &["stdot"]; "typo in deny list")]
+fn unknown_names(
+    allow: &[&str],
+    deny: &[&str],
+) {
+    let err = PluginCollector::from_selection(allow, deny)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        PluginSelectionError::Unknown {
+            name: "stdot".into()
+        }
+    );
+    assert_eq!(err.to_string(), "no plugin is registered as `stdot`");
+}
+
+#[test]
+fn from_env() {
+    // SAFETY: no other test in this binary reads or writes the environment
+    unsafe {
+        std::env::set_var("SELECTION_TEST_ENABLE", "otlp, stdout,");
+        std::env::set_var("SELECTION_TEST_DISABLE", "stdout");
+    }
+    let collected = PluginCollector::from_env().unwrap();
+    assert_eq!(keys(&collected), ["otlp"]);
+
+    // SAFETY: see above
+    unsafe {
+        std::env::remove_var("SELECTION_TEST_ENABLE");
+        std::env::set_var("SELECTION_TEST_DISABLE", "nope");
+    }
+    assert_eq!(
+        PluginCollector::from_env().err().unwrap(),
+        PluginSelectionError::Unknown {
+            name: "nope".into()
+        }
+    );
+}
@@ -0,0 +1,58 @@
+#![allow(unused)]
+use dyn_inventory::dyn_inventory;
+
+pub trait Endpoint {
+    fn path(&self) -> &'static str;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Endpoint> {
+        /// the name shown in the admin listing
+        #[serde(rename = "id")]
+        pub name: &'static str,
+        pub version: u32,
+        #[serde(skip)]
+        pub secret: &'static str,
+        handle: Handle
+    };
+    serde = true,
+}
+
+mod health {
+    use crate::{Endpoint, PluginInit};
+
+    dyn_inventory::emit! {
+        Handle Endpoint as Plugin {
+            name = "health",
+            version = 2,
+            secret = "hunter2",
+        }
+    }
+
+    impl Endpoint for Handle {
+        fn path(&self) -> &'static str {
+            "/health"
+        }
+    }
+}
+
+#[test]
+fn serializes_metadata() {
+    let collected = PluginCollector::new();
+    let expected = serde_json::json!([{ "id": "health", "version": 2 }]);
+    assert_eq!(serde_json::to_value(&collected).unwrap(), expected);
+    assert_eq!(serde_json::to_value(&collected.plugins).unwrap(), expected);
+    assert_eq!(
+        serde_json::to_value(collected.plugins[0].metadata()).unwrap(),
+        expected[0]
+    );
+}
+
+#[test]
+fn serializes_registrations() {
+    let init = PluginInit::iter().next().unwrap();
+    assert_eq!(
+        serde_json::to_value(init).unwrap(),
+        serde_json::json!({ "id": "health", "version": 2 })
+    );
+}
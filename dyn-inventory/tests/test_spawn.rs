@@ -0,0 +1,122 @@
+#![allow(unused)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use dyn_inventory::dyn_inventory;
+
+pub trait Connection {
+    fn id(&self) -> usize;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Connection> {
+        pub name: &'static str,
+        pub handle: Handle
+    };
+    key = name,
+}
+
+mod tcp {
+    use super::*;
+
+    static OPENED: AtomicUsize = AtomicUsize::new(0);
+
+    pub struct Handle(usize);
+
+    impl Handle {
+        fn open() -> Self {
+            Self(OPENED.fetch_add(1, Ordering::SeqCst))
+        }
+    }
+
+    dyn_inventory::emit! {
+        Handle Connection as Plugin {
+            name = "tcp",
+        };
+        factory = Handle::open,
+    }
+
+    impl Connection for Handle {
+        fn id(&self) -> usize {
+            self.0
+        }
+    }
+}
+
+#[test]
+fn new_instance() {
+    let collected = PluginCollector::new();
+    let tcp = collected.get("tcp").unwrap();
+    let first = tcp.handle.id();
+
+    let instance = tcp.new_instance();
+    assert_eq!(instance.key(), "tcp");
+    assert_eq!(instance.source(), tcp.source());
+    assert_eq!(instance.handle.id(), first + 1);
+
+    let spawned = collected.spawn("tcp").unwrap();
+    assert_eq!(spawned.handle.id(), first + 2);
+    assert_eq!(tcp.handle.id(), first);
+
+    assert!(collected.spawn("udp").is_none());
+}
+
+mod pooled {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[dyn_inventory::dyn_async]
+    pub trait Pool {
+        async fn size(&self) -> usize;
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Exhausted;
+
+    dyn_inventory::dyn_inventory! {
+        Pooled<Handle: Pool> {
+            pub name: &'static str,
+            pub handle: Handle
+        };
+        key = name,
+        async_factory = true,
+        error = Exhausted,
+    }
+
+    static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+    pub struct Handle(usize);
+
+    dyn_inventory::emit! {
+        Handle Pool as Pooled {
+            name = "bounded",
+        };
+        factory = || async {
+            match CREATED.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Ok(Handle(4)),
+                _ => Err(Exhausted),
+            }
+        },
+    }
+
+    #[dyn_inventory::dyn_async]
+    impl Pool for Handle {
+        async fn size(&self) -> usize {
+            self.0
+        }
+    }
+}
+
+#[test]
+fn spawn_async() {
+    use dyn_inventory_runtime::block_on;
+
+    let collected = pooled::PooledCollector::new();
+    assert_eq!(collected.plugins.len(), 1);
+
+    let spawned = block_on(collected.spawn("bounded"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(block_on(spawned.handle.size()), 4);
+
+    let exhausted = block_on(collected.spawn("bounded")).unwrap();
+    assert_eq!(exhausted.err(), Some(pooled::Exhausted));
+}
@@ -0,0 +1,164 @@
+#![allow(unused)]
+use dyn_inventory::{dyn_inventory, mock_plugin};
+
+pub trait Exporter {
+    fn name(&self) -> &'static str;
+}
+
+dyn_inventory! {
+    Plugin<Handle: Exporter> {
+        pub name: &'static str,
+        pub tags: &'static [&'static str],
+        handle: Handle
+    };
+    key = name,
+    tags = tags,
+}
+
+struct Named(&'static str);
+
+impl Exporter for Named {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+}
+
+fn plugins() -> PluginCollector {
+    PluginCollector::from_inits([
+        mock_plugin! {
+            Plugin {
+                name = "otlp-grpc",
+                tags = &["otlp", "io/network/grpc"],
+            };
+            handle = Named("otlp-grpc"),
+        },
+        mock_plugin! {
+            Plugin {
+                name = "gzip",
+                tags = &["io/compression", "io/compression/stream"],
+            };
+            handle = Named("gzip"),
+        },
+        mock_plugin! {
+            Plugin {
+                name = "otlp-http",
+                tags = &["otlp", "io/network/http"],
+            };
+            handle = Named("otlp-http"),
+        },
+        mock_plugin! {
+            Plugin {
+                name = "stdout",
+                tags = &[],
+            };
+            handle = Named("stdout"),
+        },
+    ])
+}
+
+fn names<'a>(plugins: impl IntoIterator<Item = &'a Plugin>) -> Vec<&'static str> {
+    plugins
+        .into_iter()
+        .map(Plugin::key)
+        .collect()
+}
+
+#[test]
+fn by_tag() {
+    let collected = plugins();
+    assert_eq!(names(collected.by_tag("otlp")), ["otlp-grpc", "otlp-http"]);
+    assert_eq!(names(collected.by_tag("io/compression")), ["gzip"]);
+    assert!(names(collected.by_tag("missing")).is_empty());
+}
+
+#[test]
+fn categories() {
+    let collected = plugins();
+    assert_eq!(
+        names(collected.by_tag("io")),
+        ["otlp-grpc", "gzip", "otlp-http"]
+    );
+    assert_eq!(
+        names(collected.by_tag("io/network")),
+        ["otlp-grpc", "otlp-http"]
+    );
+    // categories match whole segments only
+    assert!(names(collected.by_tag("io/net")).is_empty());
+
+    let gzip = collected.get("gzip").unwrap();
+    assert!(gzip.has_tag("io"));
+    assert!(gzip.has_tag("io/compression"));
+    assert!(!gzip.has_tag("io/comp"));
+    assert_eq!(
+        collected.tags(),
+        [
+            "io",
+            "io/compression",
+            "io/compression/stream",
+            "io/network",
+            "io/network/grpc",
+            "io/network/http",
+            "otlp"
+        ]
+    );
+}
+
+#[test]
+fn by_tags_all() {
+    let collected = plugins();
+    assert_eq!(
+        names(collected.by_tags_all(&["otlp", "io/network/http"])),
+        ["otlp-http"]
+    );
+    assert_eq!(
+        names(collected.by_tags_all(&["io", "otlp"])),
+        ["otlp-grpc", "otlp-http"]
+    );
+    assert!(names(collected.by_tags_all(&["otlp", "missing"])).is_empty());
+    // no tags at all are held by every plugin
+    assert_eq!(
+        names(collected.by_tags_all(&[])),
+        ["otlp-grpc", "gzip", "otlp-http", "stdout"]
+    );
+}
+
+#[test]
+fn by_tags_any() {
+    let collected = plugins();
+    assert_eq!(
+        names(collected.by_tags_any(&["io/compression", "otlp"])),
+        ["otlp-grpc", "gzip", "otlp-http"]
+    );
+    assert_eq!(
+        names(collected.by_tags_any(&["missing", "io/network/grpc"])),
+        ["otlp-grpc"]
+    );
+}
+
+#[test]
+fn after_changing_plugins() {
+    let mut collected = plugins();
+    collected.retain(|plugin| plugin.key() != "otlp-grpc");
+    assert_eq!(names(collected.plugins()), ["gzip", "otlp-http", "stdout"]);
+    assert_eq!(names(collected.by_tag("otlp")), ["otlp-http"]);
+    assert_eq!(names(collected.by_tags_all(&["io", "otlp"])), ["otlp-http"]);
+    assert_eq!(
+        names(collected.by_tags_any(&["io/compression", "otlp"])),
+        ["gzip", "otlp-http"]
+    );
+    assert!(!collected.tags().contains(&"io/network/grpc"));
+
+    let extra = PluginCollector::from_inits([mock_plugin! {
+        Plugin {
+            name = "zstd",
+            tags = &["io/compression"],
+        };
+        handle = Named("zstd"),
+    }]);
+    collected.extend(extra.into_plugins());
+    assert_eq!(names(collected.by_tag("io/compression")), ["gzip", "zstd"]);
+    assert_eq!(
+        names(collected.by_tags_all(&["io/compression"])),
+        ["gzip", "zstd"]
+    );
+}
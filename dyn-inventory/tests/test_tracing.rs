@@ -0,0 +1,199 @@
+#![allow(unused)]
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use dyn_inventory::dyn_inventory;
+
+pub trait Service {
+    fn name(&self) -> &'static str;
+}
+
+#[derive(Debug)]
+pub struct Unavailable;
+
+dyn_inventory! {
+    Traced<Handle: Service> {
+        pub name: &'static str,
+        pub handle: Handle
+    };
+    key = name,
+    error = Unavailable,
+}
+
+mod up {
+    use super::*;
+
+    pub struct Handle;
+
+    dyn_inventory::emit! {
+        Handle Service as Traced {
+            name = "up",
+        };
+        factory = || {
+            tracing::info!("connecting");
+            Ok(Handle)
+        },
+    }
+
+    impl Service for Handle {
+        fn name(&self) -> &'static str {
+            "up"
+        }
+    }
+}
+
+mod down {
+    use super::*;
+
+    pub struct Handle;
+
+    dyn_inventory::emit! {
+        Handle Service as Traced {
+            name = "down",
+        };
+        factory = || Err::<Handle, _>(Unavailable),
+    }
+
+    impl Service for Handle {
+        fn name(&self) -> &'static str {
+            "down"
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(
+        &mut self,
+        buf: &[u8],
+    ) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// the log lines `f` emits
+fn logged(f: impl FnOnce()) -> Vec<String> {
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .without_time()
+        .with_writer(move || writer.clone())
+        .finish();
+    tracing::subscriber::with_default(subscriber, f);
+
+    let logged = buffer.0.lock().unwrap();
+    String::from_utf8_lossy(&logged)
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+fn line<'a>(
+    logged: &'a [String],
+    needle: &str,
+) -> &'a str {
+    logged
+        .iter()
+        .find(|line| line.contains(needle))
+        .unwrap_or_else(|| panic!("`{needle}` was not logged in {logged:#?}"))
+}
+
+#[test]
+fn construction() {
+    let logged = logged(|| {
+        let collected = TracedCollector::new();
+        assert_eq!(collected.plugins.len(), 1);
+    });
+
+    // the factory runs within the span of its plugin
+    let connecting = line(&logged, "connecting");
+    assert!(
+        connecting
+            .contains(r#"collect{registry="Traced"}:construct{key="up" source=`test_tracing::up`"#)
+    );
+
+    let loaded = line(&logged, "constructed plugin");
+    assert!(loaded.starts_with("DEBUG"));
+    assert!(loaded.contains(r#"construct{key="up""#));
+    assert!(loaded.contains("outcome=Loaded elapsed="));
+
+    let failed = line(&logged, "plugin was not constructed");
+    assert!(failed.starts_with(" WARN"));
+    assert!(failed.contains(r#"construct{key="down""#));
+    assert!(failed.contains("outcome=Failed"));
+
+    let collected = line(&logged, "collected plugins");
+    assert!(collected.contains(r#"collect{registry="Traced"}"#));
+    assert!(collected.contains("registered=2 collected=1 filtered=1 elapsed="));
+}
+
+#[test]
+fn selection() {
+    let logged = logged(|| {
+        let collected = TracedCollector::from_selection(&["up"], &[]).unwrap();
+        assert_eq!(collected.plugins.len(), 1);
+    });
+
+    let selected = line(&logged, "selected plugins");
+    assert!(selected.contains(r#"registry="Traced" registered=2 selected=1"#));
+    let collected = line(&logged, "collected plugins");
+    assert!(collected.contains("registered=1 collected=1 filtered=0"));
+}
+
+mod pooled {
+    #[dyn_inventory::dyn_async]
+    pub trait Pool {
+        async fn size(&self) -> usize;
+    }
+
+    dyn_inventory::dyn_inventory! {
+        Pooled<Handle: Pool> {
+            pub name: &'static str,
+            pub handle: Handle
+        };
+        async_factory = true,
+        catch_unwind = true,
+    }
+
+    pub struct Handle;
+
+    dyn_inventory::emit! {
+        Handle Pool as Pooled {
+            name = "bounded",
+        };
+        factory = || async {
+            tracing::info!("filling");
+            Handle
+        },
+    }
+
+    #[dyn_inventory::dyn_async]
+    impl Pool for Handle {
+        async fn size(&self) -> usize {
+            1
+        }
+    }
+}
+
+#[test]
+fn async_construction() {
+    let logged = logged(|| {
+        let collected = pooled::PooledCollector::new();
+        assert_eq!(collected.plugins.len(), 1);
+    });
+
+    // unkeyed registries have no key to record
+    let filling = line(&logged, "filling");
+    assert!(filling.contains(r#"collect{registry="Pooled"}:construct{source="#));
+    line(&logged, "constructed plugin");
+}
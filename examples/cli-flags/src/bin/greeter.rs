@@ -1,3 +1,11 @@
-pub fn main() -> std::io::Result<()> {
-    abc_cli_flags::run_args(std::env::args(), &mut std::io::stdout())
+use std::process::ExitCode;
+
+pub fn main() -> ExitCode {
+    match abc_cli_flags::run_args(std::env::args(), &mut std::io::stdout()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}\n\nfor more information, try `--help`");
+            ExitCode::from(2)
+        },
+    }
 }
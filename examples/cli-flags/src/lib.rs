@@ -1,108 +1,232 @@
 use std::io::Write;
 
-pub trait Greeter {
-    fn handle(
-        &self,
-        value: String,
-        w: &mut dyn Write,
-    ) -> std::io::Result<()>;
-}
+use dyn_inventory_cli::{Cli, DynHandler, Handler, ParseError, Parsed, Spec, Value};
+
+/// the trait plugins are used through. each plugin is a [`Handler`] declaring the type of its own
+/// value, which its value is parsed as, and writes to any output.
+pub trait Greeter: for<'w> DynHandler<dyn Write + 'w, std::io::Result<()>> {}
+
+impl<H: for<'w> DynHandler<dyn Write + 'w, std::io::Result<()>>> Greeter for H {}
 
 dyn_inventory::dyn_inventory! {
     Flag<Handle: Greeter> {
-        flag_name: &'static str,
+        spec: Spec,
         flag: Handle
     };
 }
 
+impl dyn_inventory_cli::Flag for Flag {
+    fn spec(&self) -> &Spec {
+        &self.spec
+    }
+
+    fn parse(
+        &self,
+        raw: &str,
+    ) -> Result<Value, String> {
+        self.flag.parse(raw)
+    }
+}
+
 mod name {
     use crate::*;
     use dyn_inventory::emit;
 
     emit! {
         Handle Greeter as Flag {
-            flag_name = "name"
+            spec = Spec::value("name")
+                .short('n')
+                .value_name("NAME")
+                .help("greets someone by name")
+                .repeated(),
         }
     }
 
-    impl Greeter for Handle {
+    impl<'w> Handler<dyn Write + 'w> for Handle {
+        type Value = String;
+        type Output = std::io::Result<()>;
+
         fn handle(
             &self,
-            name: String,
-            w: &mut dyn Write,
+            name: &String,
+            w: &mut (dyn Write + 'w),
         ) -> std::io::Result<()> {
             writeln!(w, "Hello, {name}")
         }
     }
 }
 
-pub fn run_args<A: IntoIterator<Item = String>, W: Write>(
-    args: A,
-    wr: &mut W,
-) -> std::io::Result<()> {
-    let mut args = args.into_iter();
+mod wave {
+    use crate::*;
+    use dyn_inventory::emit;
 
-    let _ = args.next();
-    let rem: Vec<_> = args.collect();
+    emit! {
+        Handle Greeter as Flag {
+            spec = Spec::switch("wave")
+                .short('w')
+                .help("waves"),
+        }
+    }
 
-    let flags = FlagCollector::new();
+    impl<'w> Handler<dyn Write + 'w> for Handle {
+        type Value = bool;
+        type Output = std::io::Result<()>;
 
-    let mut skip = vec![];
-    for (pos, flag) in rem.iter().enumerate() {
-        if skip.contains(&pos) {
-            continue;
+        fn handle(
+            &self,
+            _: &bool,
+            w: &mut (dyn Write + 'w),
+        ) -> std::io::Result<()> {
+            writeln!(w, "*waves*")
         }
+    }
+}
 
-        let (flag, maybe_value) = maybe_value(flag.clone());
+mod age {
+    use crate::*;
+    use dyn_inventory::emit;
 
-        let value = if let Some(value) = maybe_value {
-            value
-        } else {
-            skip.push(pos + 1);
+    emit! {
+        Handle Greeter as Flag {
+            spec = Spec::value("age")
+                .value_name("YEARS")
+                .help("compliments someone on their age"),
+        }
+    }
 
-            rem.get(pos + 1)
-                .expect("value must be provided")
-                .clone()
-        };
+    impl<'w> Handler<dyn Write + 'w> for Handle {
+        type Value = u16;
+        type Output = std::io::Result<()>;
 
-        for known in &flags.plugins {
-            if flag == known.flag_name {
-                known.flag.handle(value.clone(), wr)?;
-            }
+        fn handle(
+            &self,
+            age: &u16,
+            w: &mut (dyn Write + 'w),
+        ) -> std::io::Result<()> {
+            writeln!(w, "You don't look a day over {}", age.saturating_sub(1))
         }
     }
-    Ok(())
 }
 
-fn maybe_value(flag: String) -> (String, Option<String>) {
-    let pre = flag.trim_start_matches("--");
-    if pre.contains("=") {
-        let mut split = pre.split("=");
-        let (flag, value) = (split.next().unwrap(), split.next().unwrap());
-        (flag.into(), Some(value.into()))
-    } else {
-        (pre.into(), None)
+/// the command line could not be parsed, or the output could not be written
+#[derive(Debug)]
+pub enum Error {
+    Parse(ParseError),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "{err}"),
+            Self::Io(err) => write!(f, "{err}"),
+        }
     }
 }
 
+impl std::error::Error for Error {}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+pub fn run_args<A: IntoIterator<Item = String>, W: Write>(
+    args: A,
+    wr: &mut W,
+) -> Result<(), Error> {
+    let cli = Cli::from_registry::<FlagCollector>("greeter").about("greets people");
+
+    match cli.parse(args)? {
+        Parsed::Help(help) => wr.write_all(help.as_bytes())?,
+        Parsed::Matches(matches) => {
+            for occurrence in matches.flags {
+                occurrence
+                    .flag
+                    .flag
+                    .handle(&occurrence.value, wr)?;
+            }
+        },
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
 
+    use dyn_inventory_cli::ParseError;
     use test_case::test_case;
 
+    use super::Error;
+
+    fn run(args: Vec<&str>) -> Result<String, Error> {
+        let mut buf = Vec::new();
+
+        super::run_args(args.into_iter().map(String::from), &mut buf)?;
+
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+
     #[test_case(vec!["bin", "--name=Alice"], "Hello, Alice\n"; "flag with equals")]
     #[test_case(vec!["bin", "--name", "Bob"], "Hello, Bob\n"; "flag with space")]
     #[test_case(vec!["bin", "--name=Charlie"], "Hello, Charlie\n"; "another name")]
     #[test_case(vec!["bin", "--name", "Dana"], "Hello, Dana\n"; "space variant")]
+    #[test_case(vec!["bin", "-n", "Erin"], "Hello, Erin\n"; "short alias")]
+    #[test_case(vec!["bin", "-n", "Finn", "--name", "Gail"], "Hello, Finn\nHello, Gail\n"; "repeated flag")]
+    #[test_case(vec!["bin", "--wave"], "*waves*\n"; "switch")]
+    #[test_case(vec!["bin", "-wnHal"], "*waves*\nHello, Hal\n"; "combined short aliases")]
+    #[test_case(vec!["bin", "--age", "30"], "You don't look a day over 29\n"; "typed value")]
+    #[test_case(vec!["bin"], ""; "no flags")]
+    #[test_case(vec!["bin", "--name", "-x"], "Hello, -x\n"; "dash prefixed value")]
     fn test_run_args(
         args: Vec<&str>,
         expected: &str,
     ) {
-        let mut buf = Vec::new();
+        assert_eq!(run(args).unwrap(), expected);
+    }
 
-        super::run_args(args.into_iter().map(String::from), &mut buf).unwrap();
+    #[test_case(vec!["bin", "--name"], ParseError::MissingValue { flag: "name" }; "missing value")]
+    #[test_case(vec!["bin", "--age", "-1"], ParseError::InvalidValue { flag: "age", value: "-1".into(), reason: "invalid digit found in string".into() }; "negative value")]
+    #[test_case(vec!["bin", "--age", "thirty"], ParseError::InvalidValue { flag: "age", value: "thirty".into(), reason: "invalid digit found in string".into() }; "invalid value")]
+    #[test_case(vec!["bin", "--shout"], ParseError::Unknown { flag: "--shout".into() }; "unknown flag")]
+    #[test_case(vec!["bin", "--wave=twice"], ParseError::UnexpectedValue { flag: "wave", value: "twice".into() }; "switch with value")]
+    #[test_case(vec!["bin", "-w", "-w"], ParseError::Repeated { flag: "wave" }; "repeated switch")]
+    #[test_case(vec!["bin", "Ivy"], ParseError::UnexpectedPositional { value: "Ivy".into() }; "positional")]
+    fn test_parse_errors(
+        args: Vec<&str>,
+        expected: ParseError,
+    ) {
+        match run(args) {
+            Err(Error::Parse(err)) => assert_eq!(err, expected),
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+    }
 
-        let read = String::from_utf8_lossy(&buf);
-        assert_eq!(read.into_owned(), expected);
+    #[test]
+    fn test_help() {
+        assert_eq!(
+            run(vec!["bin", "--help"]).unwrap(),
+            "\
+greets people
+
+Usage: greeter [OPTIONS]
+
+Options:
+      --age <YEARS>     compliments someone on their age
+  -n, --name <NAME>...  greets someone by name
+  -w, --wave            waves
+  -h, --help            print help
+"
+        );
     }
 }
@@ -0,0 +1,17 @@
+use abc_dylib_registry::{PluginInit, Transform};
+use dyn_inventory::emit;
+
+emit! {
+    Handle Transform as Plugin {
+        name = "lower",
+    }
+}
+
+impl Transform for Handle {
+    fn apply(
+        &self,
+        value: &str,
+    ) -> String {
+        value.to_lowercase()
+    }
+}
@@ -0,0 +1,45 @@
+use abc_dylib_registry::{PluginInit, Transform};
+use dyn_inventory::{emit, export};
+
+// the plugins of a bundled crate are exported too, once it is linked
+extern crate abc_dylib_bundled;
+
+export!(Plugin);
+
+mod shout {
+    use super::*;
+
+    emit! {
+        Handle Transform as Plugin {
+            name = "shout",
+        }
+    }
+
+    impl Transform for Handle {
+        fn apply(
+            &self,
+            value: &str,
+        ) -> String {
+            value.to_uppercase()
+        }
+    }
+}
+
+mod reverse {
+    use super::*;
+
+    emit! {
+        Handle Transform as Plugin {
+            name = "reverse",
+        }
+    }
+
+    impl Transform for Handle {
+        fn apply(
+            &self,
+            value: &str,
+        ) -> String {
+            value.chars().rev().collect()
+        }
+    }
+}
@@ -0,0 +1,51 @@
+pub trait Transform {
+    fn apply(
+        &self,
+        value: &str,
+    ) -> String;
+}
+
+#[cfg(not(feature = "next-api"))]
+dyn_inventory::dyn_inventory! {
+    Plugin<Handle: Transform> {
+        pub name: &'static str,
+        pub handle: Handle
+    };
+    key = name,
+    dylib = true,
+    api_version = "1.0",
+}
+
+// the same registry at an incompatible api version, which plugin libraries are built against to
+// test that the host sets their plugins aside
+#[cfg(feature = "next-api")]
+dyn_inventory::dyn_inventory! {
+    Plugin<Handle: Transform> {
+        pub name: &'static str,
+        pub handle: Handle
+    };
+    key = name,
+    dylib = true,
+    api_version = "2.0",
+}
+
+mod identity {
+    use dyn_inventory::emit;
+
+    use crate::{PluginInit, Transform};
+
+    emit! {
+        Handle Transform as Plugin {
+            name = "identity",
+        }
+    }
+
+    impl Transform for Handle {
+        fn apply(
+            &self,
+            value: &str,
+        ) -> String {
+            value.to_string()
+        }
+    }
+}
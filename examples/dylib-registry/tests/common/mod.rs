@@ -0,0 +1,41 @@
+use std::{
+    env::consts::{DLL_PREFIX, DLL_SUFFIX},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+};
+
+/// builds `abc-dylib-plugin` into its own target directory, as the outer build holds the lock on
+/// the workspace's.
+pub fn plugin_library() -> &'static Path {
+    static LIBRARY: OnceLock<PathBuf> = OnceLock::new();
+    LIBRARY.get_or_init(|| build(cfg!(feature = "next-api")))
+}
+
+/// builds `abc-dylib-plugin` against the registry at the api version the host is not built with.
+pub fn other_api_library() -> &'static Path {
+    static LIBRARY: OnceLock<PathBuf> = OnceLock::new();
+    LIBRARY.get_or_init(|| build(!cfg!(feature = "next-api")))
+}
+
+fn build(next_api: bool) -> PathBuf {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let (dir, features) = match next_api {
+        true => ("dylib-next-api", "abc-dylib-registry/next-api"),
+        false => ("dylib", ""),
+    };
+    let target = workspace.join("target/tests").join(dir);
+    let output = Command::new(env!("CARGO"))
+        .args(["build", "--package", "abc-dylib-plugin"])
+        .args(["--features", features])
+        .env("CARGO_TARGET_DIR", &target)
+        .current_dir(&workspace)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    target.join(format!("debug/{DLL_PREFIX}abc_dylib_plugin{DLL_SUFFIX}"))
+}
@@ -0,0 +1,155 @@
+#![allow(unused)]
+mod common;
+
+use abc_dylib_registry::{PluginCollector, PluginInit, PluginLoadError};
+use common::{other_api_library, plugin_library};
+use dyn_inventory_runtime::LoadError;
+
+#[test]
+fn load() {
+    let mut collected = PluginCollector::new();
+    assert_eq!(collected.plugins.len(), 1);
+
+    // SAFETY: the library is built from this workspace with `export!`
+    unsafe { collected.load(plugin_library()) }.unwrap();
+
+    let mut names: Vec<_> = collected
+        .plugins
+        .iter()
+        .map(|plugin| plugin.key())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["identity", "lower", "reverse", "shout"]);
+    assert_eq!(collected.libraries.len(), 1);
+
+    let shout = collected.get("shout").unwrap();
+    assert_eq!(shout.handle.apply("hello"), "HELLO");
+    assert_eq!(shout.source().package, "abc-dylib-plugin");
+
+    let lower = collected.get("lower").unwrap();
+    assert_eq!(lower.handle.apply("HELLO"), "hello");
+    assert_eq!(lower.source().package, "abc-dylib-bundled");
+}
+
+#[test]
+fn plugins_outlive_the_collector() {
+    let mut collected = PluginCollector::new();
+    // SAFETY: as above
+    unsafe { collected.load(plugin_library()) }.unwrap();
+
+    let reverse = collected
+        .plugins
+        .into_iter()
+        .find(|plugin| plugin.key() == "reverse")
+        .unwrap();
+    assert_eq!(reverse.handle.apply("abc"), "cba");
+}
+
+#[test]
+fn metadata_outlives_the_library() {
+    let (key, source) = {
+        let mut collected = PluginCollector::new();
+        // SAFETY: as above
+        unsafe { collected.load(plugin_library()) }.unwrap();
+        let shout = collected.get("shout").unwrap();
+        (shout.key(), *shout.source())
+    };
+    // the collector, its plugins and its library handles are dropped, but the library stays loaded
+    assert_eq!(key, "shout");
+    assert_eq!(source.package, "abc-dylib-plugin");
+}
+
+#[test]
+fn api_mismatch() {
+    let mut collected = PluginCollector::new();
+    // SAFETY: the library is built from this workspace with `export!`, and its plugins are never
+    // instantiated
+    unsafe { collected.load(other_api_library()) }.unwrap();
+
+    // only the linked plugin is instantiated
+    assert_eq!(collected.plugins.len(), 1);
+    let expected = PluginInit::API_VERSION.unwrap();
+    let found = if expected == "1.0" {
+        "2.0"
+    } else {
+        "1.0"
+    };
+    let mut incompatible: Vec<_> = collected
+        .incompatible
+        .iter()
+        .map(|(init, mismatch)| (init.name, mismatch.expected, mismatch.found))
+        .collect();
+    incompatible.sort();
+    assert_eq!(
+        incompatible,
+        [
+            ("lower", expected, Some(found)),
+            ("reverse", expected, Some(found)),
+            ("shout", expected, Some(found)),
+        ]
+    );
+    assert_eq!(collected.libraries.len(), 1);
+}
+
+#[test]
+fn missing_library() {
+    let mut collected = PluginCollector::new();
+    // SAFETY: the library does not exist
+    let err = unsafe { collected.load("does-not-exist.so") }.unwrap_err();
+    assert!(matches!(
+        err,
+        PluginLoadError::Library(LoadError::Open { .. })
+    ));
+}
+
+mod other {
+    pub trait Other {}
+
+    dyn_inventory::dyn_inventory! {
+        OtherPlugin<Handle: Other> {
+            pub name: &'static str,
+            pub handle: Handle
+        };
+        dylib = true,
+    }
+}
+
+#[test]
+fn missing_table() {
+    let mut collected = other::OtherPluginCollector::new();
+    // SAFETY: as above
+    let err = unsafe { collected.load(plugin_library()) }.unwrap_err();
+    assert!(matches!(
+        err,
+        other::OtherPluginLoadError::Library(LoadError::Missing { .. })
+    ));
+}
+
+mod mismatched {
+    pub trait Transform {}
+
+    // linkme can only hold one registry per name in a binary
+    dyn_inventory::dyn_inventory! {
+        Plugin<Handle: Transform> {
+            pub name: &'static str,
+            pub handle: Handle
+        };
+        dylib = true,
+        backend = inventory,
+    }
+}
+
+#[test]
+fn registry_mismatch() {
+    let mut collected = mismatched::PluginCollector::new();
+    // SAFETY: the registration table is checked before it is read
+    let err = unsafe { collected.load(plugin_library()) }.unwrap_err();
+    assert!(matches!(
+        err,
+        mismatched::PluginLoadError::Library(LoadError::Abi {
+            field: "registry",
+            ..
+        })
+    ));
+    assert!(collected.plugins.is_empty());
+}
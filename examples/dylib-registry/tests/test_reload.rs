@@ -0,0 +1,138 @@
+#![allow(unused)]
+mod common;
+
+use std::{
+    env::consts::{DLL_PREFIX, DLL_SUFFIX},
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use abc_dylib_registry::{PluginLoadError, PluginReloader};
+use common::plugin_library;
+use dyn_inventory_runtime::LoadError;
+
+fn plugin_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("abc-dylib-reload-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn install(dir: &Path) -> PathBuf {
+    let path = dir.join(format!("{DLL_PREFIX}plugin{DLL_SUFFIX}"));
+    fs::copy(plugin_library(), &path).unwrap();
+    path
+}
+
+fn touch(path: &Path) {
+    let file = fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(60))
+        .unwrap();
+}
+
+#[test]
+fn reload() {
+    let dir = plugin_dir("reload");
+    // SAFETY: the directory only holds libraries built from this workspace
+    let reloader = unsafe { PluginReloader::new(&dir) }.unwrap();
+    assert_eq!(reloader.generation(), 0);
+    assert_eq!(reloader.current().plugins.len(), 1);
+
+    let library = install(&dir);
+    assert!(reloader.changed().unwrap());
+    // SAFETY: the library is built from this workspace with `export!`
+    assert_eq!(unsafe { reloader.reload() }.unwrap(), 1);
+    assert!(!reloader.changed().unwrap());
+
+    let first = reloader.current();
+    assert_eq!(first.plugins.len(), 4);
+    assert_eq!(
+        first
+            .get("shout")
+            .unwrap()
+            .handle
+            .apply("hi"),
+        "HI"
+    );
+
+    touch(&library);
+    // SAFETY: as above
+    assert_eq!(unsafe { reloader.reload_if_changed() }.unwrap(), Some(2));
+    assert_eq!(unsafe { reloader.reload_if_changed() }.unwrap(), None);
+
+    // the previous generation remains usable while it is held
+    let second = reloader.current();
+    assert!(!std::sync::Arc::ptr_eq(&first, &second));
+    assert_eq!(
+        first
+            .get("reverse")
+            .unwrap()
+            .handle
+            .apply("abc"),
+        "cba"
+    );
+    drop(first);
+    assert_eq!(
+        second
+            .get("reverse")
+            .unwrap()
+            .handle
+            .apply("abc"),
+        "cba"
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rejected_reload_keeps_the_current_plugins() {
+    let dir = plugin_dir("rejected");
+    install(&dir);
+    // SAFETY: as above
+    let reloader = unsafe { PluginReloader::new(&dir) }.unwrap();
+    assert_eq!(reloader.current().plugins.len(), 4);
+
+    fs::write(
+        dir.join(format!("{DLL_PREFIX}broken{DLL_SUFFIX}")),
+        b"not a library",
+    )
+    .unwrap();
+    // SAFETY: the broken library can not be opened
+    assert!(unsafe { reloader.reload() }.is_err());
+    assert_eq!(reloader.generation(), 0);
+    assert_eq!(reloader.current().plugins.len(), 4);
+    assert!(reloader.changed().unwrap());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn limit() {
+    let dir = plugin_dir("limit");
+    let library = install(&dir);
+    // SAFETY: as above
+    let reloader = unsafe { PluginReloader::with_limit(&dir, 2) }.unwrap();
+    assert_eq!(reloader.loaded(), 1);
+
+    touch(&library);
+    // SAFETY: as above
+    assert_eq!(unsafe { reloader.reload() }.unwrap(), 1);
+    assert_eq!(reloader.loaded(), 2);
+
+    touch(&library);
+    // SAFETY: as above
+    let err = unsafe { reloader.reload() }.unwrap_err();
+    assert!(matches!(
+        err,
+        PluginLoadError::Library(LoadError::Limit { limit: 2, .. })
+    ));
+    assert_eq!(reloader.generation(), 1);
+    assert_eq!(reloader.loaded(), 2);
+    assert_eq!(reloader.current().plugins.len(), 4);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,119 @@
+//! a registry declared and collected by a `no_std` crate, which only requires `alloc`.
+//!
+//! plugins are collected with `linkme`, which needs neither `std` nor constructors run before
+//! `main`.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+pub trait Checksum {
+    fn digest(
+        &self,
+        data: &[u8],
+    ) -> u32;
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Unsupported(pub &'static str);
+
+dyn_inventory::dyn_inventory! {
+    Plugin<Handle: Checksum> {
+        pub name: &'static str,
+        pub depends_on: &'static [&'static str],
+        pub tags: &'static [&'static str],
+        pub handle: Handle
+    };
+    key = name,
+    depends_on = depends_on,
+    tags = tags,
+    error = Unsupported,
+    api_version = "1.0",
+    backend = linkme,
+}
+
+mod sum {
+    use crate::{Checksum, PluginInit};
+
+    dyn_inventory::emit! {
+        Handle Checksum as Plugin {
+            name = "sum",
+            depends_on = &[],
+            tags = &["simple"],
+        };
+        backend = linkme,
+    }
+
+    impl Checksum for Handle {
+        fn digest(
+            &self,
+            data: &[u8],
+        ) -> u32 {
+            data.iter().map(|byte| *byte as u32).sum()
+        }
+    }
+}
+
+mod xor {
+    use crate::{Checksum, PluginInit};
+
+    dyn_inventory::emit! {
+        Handle Checksum as Plugin {
+            name = "xor",
+            depends_on = &["sum"],
+            tags = &["simple/bitwise"],
+        };
+        backend = linkme,
+    }
+
+    impl Checksum for Handle {
+        fn digest(
+            &self,
+            data: &[u8],
+        ) -> u32 {
+            data.iter()
+                .fold(0, |acc, byte| acc ^ *byte as u32)
+        }
+    }
+}
+
+mod crc {
+    use crate::{Checksum, PluginInit, Unsupported};
+
+    pub struct Handle;
+
+    impl Handle {
+        fn new() -> Result<Self, Unsupported> {
+            Err(Unsupported("no crc table on this target"))
+        }
+    }
+
+    dyn_inventory::emit! {
+        Handle Checksum as Plugin {
+            name = "crc",
+            depends_on = &[],
+            tags = &["table"],
+        };
+        factory = Handle::new,
+        backend = linkme,
+    }
+
+    impl Checksum for Handle {
+        fn digest(
+            &self,
+            _: &[u8],
+        ) -> u32 {
+            0
+        }
+    }
+}
+
+/// the digests of `data` by every plugin which could be instantiated, in dependency order
+pub fn digests(data: &[u8]) -> Vec<(&'static str, u32)> {
+    PluginCollector::new()
+        .plugins
+        .iter()
+        .map(|plugin| (plugin.key(), plugin.handle.digest(data)))
+        .collect()
+}
@@ -0,0 +1,40 @@
+#![allow(unused)]
+use abc_no_std::{PluginCollector, PluginRegistry, Unsupported, digests};
+
+#[test]
+fn collect() {
+    // `xor` depends on `sum`, so comes after it
+    assert_eq!(digests(&[1, 2, 3]), [("sum", 6), ("xor", 0)]);
+
+    let collected = PluginCollector::new();
+    assert_eq!(collected.failed.len(), 1);
+    assert_eq!(
+        collected.failed[0].1,
+        Unsupported("no crc table on this target")
+    );
+    assert!(collected.incompatible.is_empty());
+}
+
+#[test]
+fn tags() {
+    let collected = PluginCollector::new();
+    let simple: Vec<_> = collected
+        .by_tag("simple")
+        .map(|plugin| plugin.key())
+        .collect();
+    assert_eq!(simple, ["sum", "xor"]);
+    assert_eq!(collected.tags(), ["simple", "simple/bitwise"]);
+}
+
+#[test]
+fn registry() {
+    let registry = PluginRegistry::new();
+    // `xor` depends on `sum`, so cannot be collected without it
+    let sum = registry.unregister("sum").unwrap();
+    assert!(!registry.contains("sum"));
+    assert!(registry.collect().is_err());
+
+    registry.register(sum);
+    let collected = registry.collect().unwrap();
+    assert_eq!(collected.plugins().len(), 2);
+}